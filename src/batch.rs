@@ -0,0 +1,509 @@
+use crate::conflict::{ConflictPolicy, Target};
+use crate::journal::Journal;
+use crate::path::{absolute_path, is_same_file};
+use crate::processor::encoding::escape_os_str;
+use crate::processor::symlink::Link;
+use crate::transfer::{FileAction, Verification, remove_directories, remove_path, transfer};
+use crate::{Error, Outcome, Renamed, Report, ReportEntry};
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const TEMPORARY_SUFFIX: &str = ".renamer-tmp";
+
+/// Used with [`Collision`] to indicate why a new path is not safe to rename to
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum CollisionKind {
+    /// More than one item is renamed to the same path
+    Duplicate,
+    /// The path already exists and is not being renamed away by another item
+    Exists,
+    /// More than one item is renamed to paths that only differ by case, which collide on case-insensitive file systems
+    CaseOnly,
+}
+
+/// Represents a new path in a [`BatchBuilder`] that would overwrite another file
+#[derive(Debug, Clone)]
+pub struct Collision {
+    kind: CollisionKind,
+    path: PathBuf,
+    originals: Vec<PathBuf>,
+}
+
+/// A [`BatchBuilder`] is used to apply the [`Renamed`] items produced by a [`ProcessorBuilder`][crate::ProcessorBuilder] as a whole
+///
+/// Items are applied deepest path first so renaming a directory does not invalidate the paths of items inside it.
+#[derive(Debug)]
+pub struct BatchBuilder {
+    conflict_policy: ConflictPolicy,
+    journal: Option<PathBuf>,
+    renamed: Vec<Box<dyn Renamed>>,
+    verification: Verification,
+}
+
+impl BatchBuilder {
+    /// Constructs new [`BatchBuilder`]
+    pub fn new(renamed: Vec<Box<dyn Renamed>>) -> Self {
+        Self {
+            conflict_policy: ConflictPolicy::default(),
+            journal: None,
+            renamed,
+            verification: Verification::default(),
+        }
+    }
+
+    /// Sets the [`ConflictPolicy`] used when the new path of an item already exists
+    pub fn conflict_policy(mut self, conflict_policy: ConflictPolicy) -> Self {
+        self.conflict_policy = conflict_policy;
+        self
+    }
+
+    /// Sets the path of a journal file that each rename is appended to as a JSON line so it can be reversed with [`BatchBuilder::undo`]
+    ///
+    /// Batches applied with the same journal are appended to it until it is undone.
+    pub fn journal<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.journal = Some(path.as_ref().into());
+        self
+    }
+
+    /// Sets the [`Verification`] of files copied when they are moved to another file system
+    pub fn verification(mut self, verification: Verification) -> Self {
+        self.verification = verification;
+        self
+    }
+
+    /// Reverse the renames recorded in a journal file written by [`BatchBuilder::apply`] and clear it
+    ///
+    /// Every batch recorded in the journal is reversed, latest first. Copies and links created with a [`FileAction`]
+    /// other than [`FileAction::Move`] are removed. Symbolic links that cannot be pointed back at an original path are
+    /// logged.
+    ///
+    /// Nothing is renamed when a renamed file was changed, moved or replaced since it was recorded, or an original path
+    /// is in use, instead [`Error::JournalConflicts`] lists the paths.
+    pub fn undo<P: AsRef<Path>>(journal: P) -> Result<(), Error> {
+        run(
+            crate::journal::undo_steps(journal.as_ref())?,
+            None,
+            Verification::default(),
+        )?;
+        crate::journal::clear(journal.as_ref())
+    }
+
+    /// Return the [`Renamed`] items in the batch
+    pub fn renamed(&self) -> &[Box<dyn Renamed>] {
+        self.renamed.as_slice()
+    }
+
+    /// Return every [`Collision`] between the new paths of the items in the batch and existing files
+    pub fn collisions(&self) -> Vec<Collision> {
+        let files: Vec<_> = self.renamed.iter().filter_map(|r| r.as_file()).collect();
+        let originals: HashSet<_> = files
+            .iter()
+            .filter(|f| !f.file_action().keeps_source())
+            .map(|f| absolute_path(f.original_path()))
+            .collect();
+
+        let mut futures: IndexMap<_, Vec<PathBuf>> = IndexMap::new();
+        for file in files.iter() {
+            futures
+                .entry(absolute_path(file.new_path()))
+                .or_default()
+                .push(file.original_path().into());
+        }
+
+        let mut collisions = Vec::new();
+        let mut folded: IndexMap<_, Vec<&Path>> = IndexMap::new();
+        for (path, sources) in futures.iter() {
+            folded
+                .entry(path.to_string_lossy().to_lowercase())
+                .or_default()
+                .push(path.as_ref());
+            if sources.len() > 1 {
+                collisions.push(Collision::new(
+                    CollisionKind::Duplicate,
+                    path,
+                    sources.clone(),
+                ));
+            } else if !originals.contains(path) && path.symlink_metadata().is_ok() {
+                let same_file = sources
+                    .iter()
+                    .any(|source| is_same_file(source.as_path(), path.as_ref()));
+                if !same_file {
+                    collisions.push(Collision::new(CollisionKind::Exists, path, sources.clone()));
+                }
+            }
+        }
+        for paths in folded.values().filter(|paths| paths.len() > 1) {
+            for path in paths {
+                let sources = futures.get(*path).cloned().unwrap_or_default();
+                collisions.push(Collision::new(CollisionKind::CaseOnly, path, sources));
+            }
+        }
+        collisions
+    }
+
+    /// Take the action for every item in the batch, stopping at the first error, and report the [`Outcome`] of each
+    ///
+    /// Items that are not files are applied on their own once the files are renamed, a failure is reported as
+    /// [`Outcome::Failed`].
+    ///
+    /// Nothing is renamed when any [`Collision`] is found, unless a [`ConflictPolicy`] other than
+    /// [`ConflictPolicy::Fail`] resolves them. When a rename fails every rename already done is reversed
+    /// and [`Error::RolledBack`] lists them. A symbolic link that cannot be pointed at its renamed target does not
+    /// stop the batch, the item is reported as [`Outcome::LinkNotUpdated`]. Chains and cycles of renames, such as swapping two names,
+    /// are moved through temporary names so no file is overwritten.
+    pub fn apply(&self) -> Result<Report, Error> {
+        if self.conflict_policy == ConflictPolicy::Fail {
+            let collisions = self.collisions();
+            if !collisions.is_empty() {
+                return Err(Error::Collisions(collisions));
+            }
+        }
+        let files: Vec<_> = self.renamed.iter().filter_map(|r| r.as_file()).collect();
+        let (targets, skipped) = self.conflict_policy.resolve(files.as_slice())?;
+        let skipped: HashMap<_, _> = skipped
+            .into_iter()
+            .map(|(file, reason)| (file.original_path(), reason))
+            .collect();
+        let futures: HashMap<_, _> = targets
+            .iter()
+            .filter(|t| t.path != t.file.new_path())
+            .map(|t| (t.file.original_path(), resolved_future(t)))
+            .collect();
+        let journal = match &self.journal {
+            Some(path) => Some(Journal::open(path)?),
+            None => None,
+        };
+        let paths: HashMap<_, _> = targets
+            .iter()
+            .map(|t| (t.file.original_path(), t.path.clone()))
+            .collect();
+        let mut unlinked: HashMap<_, _> = run(self.plan(targets)?, journal, self.verification)?
+            .into_iter()
+            .collect();
+
+        let mut report = Vec::with_capacity(self.renamed.len());
+        for renamed in self.renamed.iter() {
+            let outcome = match renamed.as_file() {
+                _ if renamed.skip_reason().is_some() || renamed.is_unchanged() => renamed.apply(),
+                Some(file) => match skipped.get(file.original_path()) {
+                    Some(reason) => Outcome::Skipped(reason.clone()),
+                    None => match paths
+                        .get(file.original_path())
+                        .and_then(|p| unlinked.remove(p))
+                    {
+                        Some(error) => Outcome::LinkNotUpdated(error),
+                        None => Outcome::Renamed,
+                    },
+                },
+                None => renamed.apply(),
+            };
+            let entry = ReportEntry::new(renamed.as_ref(), outcome);
+            report.push(
+                match renamed
+                    .as_file()
+                    .and_then(|f| futures.get(f.original_path()))
+                {
+                    Some(future) => entry.with_future(future.clone()),
+                    None => entry,
+                },
+            );
+        }
+        Ok(report.into_iter().collect())
+    }
+
+    /// Return the renames needed to apply the file items in the batch
+    ///
+    /// Existing files that are overwritten are moved aside first. Items are moved deepest path first. An item whose new
+    /// path is still held by another item is moved once that item has moved, and when every remaining item at a depth
+    /// is waiting on another one at that depth, as in a cycle, one of them is moved to a temporary name before items at
+    /// other depths move.
+    fn plan(&self, mut targets: Vec<Target>) -> Result<Vec<Step>, Error> {
+        targets.sort_by_key(|t| std::cmp::Reverse(depth(t.file.original_path())));
+
+        let mut pending: IndexMap<PathBuf, (PathBuf, Target)> = targets
+            .into_iter()
+            .map(|t| {
+                (
+                    absolute_path(t.file.original_path()).into(),
+                    (t.file.original_path().into(), t),
+                )
+            })
+            .collect();
+        let mut steps = Vec::with_capacity(pending.len());
+        for (_, target) in pending.values().filter(|(_, t)| t.overwrite) {
+            steps.push(Step {
+                from: target.path.clone(),
+                to: temporary_path(target.path.as_path(), |p| is_pending(&pending, p))?,
+                link: None,
+                action: FileAction::Move,
+                discard: true,
+            });
+        }
+        while let Some((_, (_, first))) = pending.first() {
+            let level = depth(first.file.original_path());
+            let level_count = pending
+                .values()
+                .take_while(|(_, t)| depth(t.file.original_path()) == level)
+                .count();
+            // Index of the moved item still holding the new path of the target
+            let blocker = |target: &Target| {
+                pending
+                    .get_full(absolute_path(target.path.as_path()).as_ref())
+                    .filter(|(_, _, (_, t))| !t.file.file_action().keeps_source())
+                    .map(|(index, _, _)| index)
+            };
+            // A cycle at the deepest level is broken before items at other levels move
+            let cycle = pending
+                .values()
+                .take(level_count)
+                .find_map(|(_, t)| blocker(t).filter(|index| *index < level_count));
+            let ready = pending
+                .values()
+                .take(level_count)
+                .position(|(_, t)| blocker(t).is_none())
+                .or_else(|| match cycle {
+                    Some(_) => None,
+                    None => pending.values().position(|(_, t)| blocker(t).is_none()),
+                });
+            match ready {
+                Some(index) => {
+                    let (_, (from, target)) = pending
+                        .shift_remove_index(index)
+                        .expect("index is within pending items");
+                    steps.push(Step {
+                        from,
+                        to: target.path,
+                        link: target.file.link_path().map(Into::into),
+                        action: target.file.file_action(),
+                        discard: false,
+                    });
+                }
+                None => {
+                    let index = cycle
+                        .or_else(|| {
+                            pending
+                                .values()
+                                .position(|(_, t)| !t.file.file_action().keeps_source())
+                        })
+                        .expect("only moved items block others");
+                    let (_, (from, target)) = pending
+                        .shift_remove_index(index)
+                        .expect("index is within pending items");
+                    let temporary = temporary_path(from.as_path(), |p| is_pending(&pending, p))?;
+                    pending.insert_before(
+                        index,
+                        absolute_path(temporary.as_path()).into(),
+                        (temporary.clone(), target),
+                    );
+                    steps.push(Step {
+                        from,
+                        to: temporary,
+                        link: None,
+                        action: FileAction::Move,
+                        discard: false,
+                    });
+                }
+            }
+        }
+        Ok(steps)
+    }
+}
+
+/// A single rename in the plan of a batch
+///
+/// The link is the symbolic link pointed at the destination once it has moved, which is only set on the rename that
+/// moves an item to its new path. Steps with an action other than [`FileAction::Move`] leave the source in place.
+/// Discarded steps move an overwritten file, or a copy or link being undone, aside, it is removed once every step
+/// succeeded and is not recorded in the journal.
+#[derive(Debug, Clone)]
+pub(crate) struct Step {
+    pub(crate) from: PathBuf,
+    pub(crate) to: PathBuf,
+    pub(crate) link: Option<PathBuf>,
+    pub(crate) action: FileAction,
+    pub(crate) discard: bool,
+}
+
+impl Step {
+    fn revert(&self, verification: Verification) -> Result<(), Error> {
+        match self.action.keeps_source() {
+            true => remove_path(self.to.as_path()),
+            false => {
+                transfer(
+                    self.to.as_path(),
+                    self.from.as_path(),
+                    FileAction::Move,
+                    verification,
+                )?;
+                Ok(())
+            }
+        }
+    }
+
+    fn point_link(&self, target: &Path) -> Result<(), Error> {
+        if let Some(link) = &self.link {
+            Link::read(link)
+                .and_then(|l| l.update(target))
+                .map_err(|source| Error::LinkNotUpdated {
+                    link: link.clone(),
+                    source,
+                })?;
+        }
+        Ok(())
+    }
+}
+
+/// Apply the steps in order, recording each in the journal, and reverse them all when one fails
+///
+/// Returns the destination of each step whose symbolic link could not be updated with the error.
+pub(crate) fn run(
+    steps: Vec<Step>,
+    mut journal: Option<Journal>,
+    verification: Verification,
+) -> Result<Vec<(PathBuf, Error)>, Error> {
+    let mut completed: Vec<(Step, Vec<PathBuf>)> = Vec::with_capacity(steps.len());
+    let mut unlinked = Vec::new();
+    for step in steps {
+        match transfer(
+            step.from.as_path(),
+            step.to.as_path(),
+            step.action,
+            verification,
+        ) {
+            Ok(created) => completed.push((step, created)),
+            Err(error) => return Err(rollback(completed, journal, error, verification)),
+        }
+        let (step, _) = completed.last().expect("step was pushed");
+        // The rename is kept when the link cannot be pointed at it
+        if let Err(error) = step.point_link(step.to.as_path()) {
+            log::error!("{error}");
+            unlinked.push((step.to.clone(), error));
+        }
+        let recorded = match (journal.as_mut(), step.discard) {
+            (Some(journal), false) => journal.record(step),
+            _ => Ok(()),
+        };
+        if let Err(error) = recorded {
+            return Err(rollback(completed, journal, error, verification));
+        }
+    }
+    for (step, _) in completed.iter().filter(|(s, _)| s.discard) {
+        if let Err(e) = remove_path(step.to.as_path()) {
+            log::warn!("Unable to remove discarded {:?}: {e}", step.to);
+        }
+    }
+    Ok(unlinked)
+}
+
+// Reverse the completed renames, latest first, and wrap the error that stopped the batch
+fn rollback(
+    completed: Vec<(Step, Vec<PathBuf>)>,
+    journal: Option<Journal>,
+    error: Error,
+    verification: Verification,
+) -> Error {
+    if let Some(Err(e)) = journal.map(|j| j.discard()) {
+        log::error!("Unable to discard journal entries: {e}");
+    }
+    let mut rolled_back = Vec::with_capacity(completed.len());
+    let mut failed = Vec::new();
+    let mut unlinked = Vec::new();
+    for (step, created) in completed.into_iter().rev() {
+        match step.revert(verification) {
+            Ok(_) => {
+                remove_directories(created.as_slice());
+                // The rename is reversed even when the link cannot be pointed back at it
+                if let (Err(e), Some(link)) = (step.point_link(step.from.as_path()), &step.link) {
+                    log::error!("{e}");
+                    unlinked.push(link.clone());
+                }
+                rolled_back.push((step.from, step.to));
+            }
+            Err(e) => {
+                log::error!("Unable to roll back {:?} to {:?}: {e}", step.to, step.from);
+                failed.push((step.from, step.to));
+            }
+        }
+    }
+    Error::RolledBack {
+        source: Box::new(error),
+        rolled_back,
+        failed,
+        unlinked,
+    }
+}
+
+// Whether an item in the plan is still at the path
+fn is_pending(pending: &IndexMap<PathBuf, (PathBuf, Target)>, path: &Path) -> bool {
+    pending.contains_key(absolute_path(path).as_ref())
+}
+
+/// Unused path next to the provided one to move an item out of the way of others, skipping paths that are reserved
+pub(crate) fn temporary_path<F: Fn(&Path) -> bool>(
+    path: &Path,
+    reserved: F,
+) -> Result<PathBuf, Error> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    (0..u16::MAX)
+        .map(|i| {
+            path.with_file_name(format!(
+                ".{name}.{}.{i}{TEMPORARY_SUFFIX}",
+                std::process::id()
+            ))
+        })
+        .find(|p| p.symlink_metadata().is_err() && !reserved(p))
+        .ok_or_else(|| {
+            Error::InvalidValue(format!(
+                "No temporary name available for {}",
+                path.display()
+            ))
+        })
+}
+
+impl Collision {
+    pub(crate) fn new<P: AsRef<Path>>(
+        kind: CollisionKind,
+        path: P,
+        originals: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            kind,
+            path: path.as_ref().into(),
+            originals,
+        }
+    }
+
+    /// Return the [`CollisionKind`]
+    pub fn kind(&self) -> CollisionKind {
+        self.kind
+    }
+
+    /// Return the new path that collides
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// Return the original paths of the items renamed to the path
+    pub fn originals(&self) -> &[PathBuf] {
+        self.originals.as_slice()
+    }
+}
+
+// The new name of the item with the file name of its resolved path, which differs when a suffix was added
+fn resolved_future(target: &Target) -> String {
+    let name = target.path.file_name().unwrap_or_default();
+    let name = match name.to_str() {
+        Some(name) => name.to_string(),
+        None => escape_os_str(name),
+    };
+    match target.file.future().rsplit_once('/') {
+        Some((directory, _)) => format!("{directory}/{name}"),
+        None => name,
+    }
+}
+
+// Number of path components of the absolute path
+fn depth(path: &Path) -> usize {
+    absolute_path(path).components().count()
+}
@@ -0,0 +1,185 @@
+use crate::batch::{Collision, CollisionKind};
+use crate::path::{absolute_path, is_same_file};
+use crate::{Error, RenamedFile};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+const EXTENSION_SEPARATOR: char = '.';
+
+/// Used with the [`BatchBuilder`][crate::BatchBuilder] to choose what happens when the new path of an item already exists
+///
+/// Items renamed to the same path as an earlier item in the batch are handled the same way, except with
+/// [`ConflictPolicy::Overwrite`] and [`ConflictPolicy::KeepNewer`] which fail as the earlier item would be lost.
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum ConflictPolicy {
+    /// Do not rename anything and return [`Error::Collisions`]
+    #[default]
+    Fail,
+    /// Do not rename the item
+    Skip,
+    /// Replace the existing file, it is only removed once every rename in the batch succeeded
+    Overwrite,
+    /// Replace the existing file when the item was modified more recently, otherwise do not rename the item
+    KeepNewer,
+    /// Rename the item to the first free path with ` (1)`, ` (2)` and so on added before the extension
+    AutoSuffix,
+}
+
+/// The new path of an item once conflicts are resolved
+#[derive(Debug)]
+pub(crate) struct Target<'a> {
+    pub(crate) file: &'a RenamedFile,
+    pub(crate) path: PathBuf,
+    pub(crate) overwrite: bool,
+}
+
+/// The items that are renamed and the items that are skipped with the reason
+pub(crate) type Resolution<'a> = (Vec<Target<'a>>, Vec<(&'a RenamedFile, String)>);
+
+impl ConflictPolicy {
+    /// Resolve the new path of every item that changes and return the items that are skipped with the reason
+    pub(crate) fn resolve<'a>(&self, files: &[&'a RenamedFile]) -> Result<Resolution<'a>, Error> {
+        let changed: Vec<_> = files
+            .iter()
+            .filter(|f| absolute_path(f.original_path()) != absolute_path(f.new_path()))
+            .copied()
+            .collect();
+        let mut active: HashSet<PathBuf> = changed
+            .iter()
+            .map(|f| absolute_path(f.original_path()).into())
+            .collect();
+
+        // A skipped item stays where it is so items renamed to its path are resolved again
+        let mut all_skipped = Vec::new();
+        loop {
+            let (targets, skipped) = self.resolve_active(changed.as_slice(), &active)?;
+            if skipped.is_empty() {
+                return Ok((targets, all_skipped));
+            }
+            for (file, _) in skipped.iter() {
+                active.remove(absolute_path(file.original_path()).as_ref());
+            }
+            all_skipped.extend(skipped);
+        }
+    }
+
+    fn resolve_active<'a>(
+        &self,
+        files: &[&'a RenamedFile],
+        active: &HashSet<PathBuf>,
+    ) -> Result<Resolution<'a>, Error> {
+        let vacating: HashSet<PathBuf> = files
+            .iter()
+            .filter(|f| !f.file_action().keeps_source())
+            .map(|f| absolute_path(f.original_path()).into())
+            .filter(|p| active.contains(p))
+            .collect();
+        let mut claimed = HashSet::new();
+        let mut targets = Vec::with_capacity(files.len());
+        let mut skipped = Vec::new();
+        let mut duplicates = Vec::new();
+        for file in files
+            .iter()
+            .filter(|f| active.contains(absolute_path(f.original_path()).as_ref()))
+        {
+            let path = file.new_path().to_path_buf();
+            let taken = claimed.contains(&folded(path.as_path()));
+            let exists = !taken && occupied(file, path.as_path(), &vacating);
+            let target = match (self, taken, exists) {
+                (_, false, false) => Ok((path, false)),
+                (ConflictPolicy::Fail, _, _) => {
+                    return Err(Error::Collisions(vec![Collision::new(
+                        CollisionKind::Exists,
+                        path,
+                        vec![file.original_path().into()],
+                    )]));
+                }
+                (ConflictPolicy::Overwrite | ConflictPolicy::KeepNewer, true, _) => {
+                    duplicates.push(Collision::new(
+                        CollisionKind::Duplicate,
+                        path,
+                        vec![file.original_path().into()],
+                    ));
+                    continue;
+                }
+                (ConflictPolicy::Skip, true, _) => Err(format!(
+                    "new path {} is used by an earlier item",
+                    path.display()
+                )),
+                (ConflictPolicy::Skip, _, _) => {
+                    Err(format!("new path {} already exists", path.display()))
+                }
+                (ConflictPolicy::Overwrite, _, _) => Ok((path, true)),
+                (ConflictPolicy::KeepNewer, _, _) => {
+                    match is_newer(file.original_path(), path.as_path())? {
+                        true => Ok((path, true)),
+                        false => Err(format!("existing file {} is not older", path.display())),
+                    }
+                }
+                (ConflictPolicy::AutoSuffix, _, _) => Ok((
+                    (1..)
+                        .map(|n| suffixed(file, n))
+                        .find(|p| !claimed.contains(&folded(p)) && !occupied(file, p, &vacating))
+                        .expect("a free suffix exists"),
+                    false,
+                )),
+            };
+            match target {
+                Ok((path, overwrite)) => {
+                    claimed.insert(folded(path.as_path()));
+                    targets.push(Target {
+                        file,
+                        path,
+                        overwrite,
+                    });
+                }
+                Err(reason) => {
+                    log::info!("Skipping {:?}: {reason}", file.original_path());
+                    skipped.push((*file, reason));
+                }
+            }
+        }
+        match duplicates.is_empty() {
+            true => Ok((targets, skipped)),
+            false => Err(Error::Collisions(duplicates)),
+        }
+    }
+}
+
+// Whether the path exists and is not moved away by another item in the batch
+fn occupied(file: &RenamedFile, path: &Path, vacating: &HashSet<PathBuf>) -> bool {
+    path.symlink_metadata().is_ok()
+        && !vacating.contains(absolute_path(path).as_ref())
+        && !is_same_file(file.original_path(), path)
+}
+
+// Key used to find new paths that only differ by case
+fn folded(path: &Path) -> String {
+    absolute_path(path).to_string_lossy().to_lowercase()
+}
+
+fn is_newer(source: &Path, destination: &Path) -> Result<bool, Error> {
+    Ok(source.symlink_metadata()?.modified()? > destination.symlink_metadata()?.modified()?)
+}
+
+// New path of the item with ` (n)` added before the extension, or at the end of directory names
+fn suffixed(file: &RenamedFile, n: usize) -> PathBuf {
+    let name = file.new_path().file_name().unwrap_or_default();
+    let suffix = format!(" ({n})");
+    let suffixed = match name.to_str() {
+        Some(name) if !file.original_path().is_dir() => match name.rfind(EXTENSION_SEPARATOR) {
+            Some(i) if i > 0 => {
+                let (stem, extension) = name.split_at(i);
+                OsString::from(format!("{stem}{suffix}{extension}"))
+            }
+            _ => OsString::from(format!("{name}{suffix}")),
+        },
+        _ => {
+            let mut name = name.to_os_string();
+            name.push(suffix);
+            name
+        }
+    };
+    file.new_path().with_file_name(suffixed)
+}
@@ -1,4 +1,6 @@
+use crate::batch::Collision;
 use std::num::ParseIntError;
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// Errors returned by pass-it-on-command-line-client
@@ -12,6 +14,48 @@ pub enum Error {
     #[error("Invalid Value: {0}")]
     InvalidValue(String),
 
+    /// Applying a [`BatchBuilder`][crate::BatchBuilder] would overwrite files
+    #[error("{} new paths collide with other files: {:?}", .0.len(), .0.iter().map(|c| c.path()).collect::<Vec<_>>())]
+    Collisions(Vec<Collision>),
+
+    /// Applying a [`BatchBuilder`][crate::BatchBuilder] failed and the renames already done were reversed
+    #[error("Batch failed and {} renames were rolled back ({} could not be): {source}", .rolled_back.len(), .failed.len())]
+    RolledBack {
+        /// The error that stopped the batch
+        source: Box<Error>,
+        /// The `(from, to)` paths of renames that were reversed, latest first
+        rolled_back: Vec<(PathBuf, PathBuf)>,
+        /// The `(from, to)` paths of renames that could not be reversed
+        failed: Vec<(PathBuf, PathBuf)>,
+        /// Symbolic links that could not be pointed back at the original path of a reversed rename
+        unlinked: Vec<PathBuf>,
+    },
+
+    /// An item was renamed but the symbolic link pointing at it could not be updated
+    #[error("Renamed but the symbolic link {} could not be updated: {source}", .link.display())]
+    LinkNotUpdated {
+        /// Path of the symbolic link
+        link: PathBuf,
+        /// The error updating the link
+        source: std::io::Error,
+    },
+
+    /// Files recorded in a journal were changed since it was written or their original paths are in use
+    #[error("Files changed since the journal was written: {0:?}")]
+    JournalConflicts(Vec<PathBuf>),
+
+    /// A file copied to another file system does not match the source, which is kept
+    #[error("Copied file does not match the source: {}", .0.display())]
+    VerificationFailed(PathBuf),
+
+    /// A glob pattern input did not match any paths
+    #[error("No paths matched the glob pattern: {0}")]
+    NoGlobMatches(String),
+
+    /// A file name is not valid UTF-8 and [`NonUtf8Policy::Error`][crate::NonUtf8Policy::Error] is in use
+    #[error("File name is not valid UTF-8: {}", .0.display())]
+    NonUtf8FileName(PathBuf),
+
     /// Error determining [`FormatType`][crate::FormatType] when processing found formating patterns
     #[error("Unknown Format Type: {0}")]
     UnknownFormatType(String),
@@ -25,6 +69,22 @@ pub enum Error {
     #[error("regex Error: {0}")]
     RegEx(#[from] regex::Error),
 
+    /// Pass-thru [`aho_corasick::BuildError`].
+    #[error("aho_corasick Error: {0}")]
+    AhoCorasick(#[from] aho_corasick::BuildError),
+
+    /// Pass-thru [`globset::Error`].
+    #[error("globset Error: {0}")]
+    Glob(#[from] globset::Error),
+
+    /// Pass-thru [`walkdir::Error`].
+    #[error("walkdir Error: {0}")]
+    Walk(#[from] walkdir::Error),
+
+    /// Pass-thru [`serde_json::Error`].
+    #[error("serde_json Error: {0}")]
+    Json(#[from] serde_json::Error),
+
     /// Pass-thru [`ParseIntError`].
     #[error("ParseIntError Error: {0}")]
     ParseInt(#[from] ParseIntError),
@@ -0,0 +1,193 @@
+use crate::batch::{Step, temporary_path};
+use crate::error::Error;
+use crate::path::absolute_path;
+use crate::processor::encoding::{escape_os_str, unescape_os_str};
+use crate::transfer::FileAction;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// A single rename recorded in the journal, paths are escaped as in [`NonUtf8Policy::Escape`][crate::NonUtf8Policy::Escape]
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    from: String,
+    to: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    link: Option<String>,
+    #[serde(default)]
+    action: FileAction,
+    /// Seconds since the Unix epoch when the rename was done
+    time: u64,
+    /// Size of the renamed file, not set for directories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    len: Option<u64>,
+    /// Modification time of the renamed file, not set for directories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<Duration>,
+}
+
+/// Journal file that the renames of a batch are appended to
+#[derive(Debug)]
+pub(crate) struct Journal {
+    file: File,
+    start: u64,
+}
+
+impl Journal {
+    /// Open the journal file for appending, creating it when it does not exist
+    pub(crate) fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())?;
+        let start = file.metadata()?.len();
+        Ok(Self { file, start })
+    }
+
+    /// Append a completed rename to the journal
+    pub(crate) fn record(&mut self, step: &Step) -> Result<(), Error> {
+        let (len, modified) = file_state(step.to.as_path())?;
+        let entry = Entry {
+            from: escape_os_str(absolute_path(step.from.as_path()).as_os_str()),
+            to: escape_os_str(absolute_path(step.to.as_path()).as_os_str()),
+            action: step.action,
+            link: step
+                .link
+                .as_ref()
+                .map(|l| escape_os_str(absolute_path(l).as_os_str())),
+            time: since_epoch(SystemTime::now()).as_secs(),
+            len,
+            modified,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    /// Remove the entries recorded since the journal was opened
+    pub(crate) fn discard(self) -> Result<(), Error> {
+        self.file.set_len(self.start)?;
+        Ok(())
+    }
+}
+
+/// Return the steps reversing every entry in the journal, latest first
+///
+/// Every renamed file is checked against its recorded size and modification time, and every original path must be free.
+/// Paths created by actions that keep the source are moved aside from where they are at that point in the sequence and
+/// only removed once every step succeeded.
+pub(crate) fn undo_steps(path: &Path) -> Result<Vec<Step>, Error> {
+    let mut entries = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            entries.push(serde_json::from_str::<Entry>(line.as_str())?);
+        }
+    }
+
+    // Replay the journal to find where each file is now and which original paths are left empty, paths inside a
+    // renamed directory move with it
+    let mut current: IndexMap<PathBuf, &Entry> = IndexMap::new();
+    let mut vacated: Vec<PathBuf> = Vec::new();
+    for entry in entries.iter() {
+        let from = PathBuf::from(unescape_os_str(entry.from.as_str()));
+        let to = PathBuf::from(unescape_os_str(entry.to.as_str()));
+        if entry.action.keeps_source() {
+            current.insert(to, entry);
+            continue;
+        }
+        current = current
+            .into_iter()
+            .filter(|(path, _)| *path != from)
+            .map(|(path, e)| (moved(path, from.as_path(), to.as_path()), e))
+            .collect();
+        vacated = vacated
+            .into_iter()
+            .filter(|path| *path != to)
+            .map(|path| moved(path, from.as_path(), to.as_path()))
+            .collect();
+        current.insert(to, entry);
+        vacated.push(from);
+    }
+
+    let mut conflicts = Vec::new();
+    for (path, entry) in current.iter() {
+        let unchanged = match path.symlink_metadata() {
+            Ok(metadata) if metadata.is_dir() => entry.len.is_none(),
+            Ok(_) => file_state(path).is_ok_and(|state| state == (entry.len, entry.modified)),
+            Err(_) => false,
+        };
+        if !unchanged {
+            conflicts.push(path.clone());
+        }
+    }
+    conflicts.extend(vacated.into_iter().filter(|p| p.symlink_metadata().is_ok()));
+    if !conflicts.is_empty() {
+        return Err(Error::JournalConflicts(conflicts));
+    }
+
+    let mut reserved = HashSet::new();
+    let mut steps = Vec::with_capacity(entries.len());
+    for entry in entries.iter().rev() {
+        let from = PathBuf::from(unescape_os_str(entry.from.as_str()));
+        let to = PathBuf::from(unescape_os_str(entry.to.as_str()));
+        steps.push(match entry.action.keeps_source() {
+            true => {
+                let temporary = temporary_path(to.as_path(), |p| reserved.contains(p))?;
+                reserved.insert(temporary.clone());
+                Step {
+                    from: to,
+                    to: temporary,
+                    link: None,
+                    action: FileAction::Move,
+                    discard: true,
+                }
+            }
+            false => Step {
+                from: to,
+                to: from,
+                link: entry.link.as_ref().map(|l| unescape_os_str(l).into()),
+                action: FileAction::Move,
+                discard: false,
+            },
+        });
+    }
+    Ok(steps)
+}
+
+/// Remove every entry from the journal once it was undone
+pub(crate) fn clear(path: &Path) -> Result<(), Error> {
+    OpenOptions::new().write(true).open(path)?.set_len(0)?;
+    Ok(())
+}
+
+// Path after the directory at `from` is renamed to `to`
+fn moved(path: PathBuf, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rest) if !rest.as_os_str().is_empty() => to.join(rest),
+        _ => path,
+    }
+}
+
+// Size and modification time of a file, directories have neither as their contents change while renaming
+fn file_state(path: &Path) -> Result<(Option<u64>, Option<Duration>), Error> {
+    let metadata = path.symlink_metadata()?;
+    Ok(match metadata.is_dir() {
+        true => (None, None),
+        false => (
+            Some(metadata.len()),
+            Some(since_epoch(metadata.modified()?)),
+        ),
+    })
+}
+
+fn since_epoch(time: SystemTime) -> Duration {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+}
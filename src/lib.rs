@@ -16,16 +16,37 @@
 //!     
 //! ```
 
+mod batch;
+mod conflict;
 mod error;
+mod journal;
+mod outcome;
+mod path;
 mod processor;
+mod transfer;
 
+pub use crate::batch::{BatchBuilder, Collision, CollisionKind};
+pub use crate::conflict::ConflictPolicy;
 pub use crate::error::Error;
+pub use crate::outcome::{Outcome, Report, ReportEntry, Summary};
 pub use crate::processor::ProcessorBuilder;
+pub use crate::processor::dedup::Deduplicate;
 pub use crate::processor::delimiter::{Delimiter, DelimiterType};
+pub use crate::processor::dictionary::DictionaryMatch;
+pub use crate::processor::encoding::NonUtf8Policy;
 pub use crate::processor::extractor::Extractor;
+pub use crate::processor::filter::{FileKind, InputFilter};
 pub use crate::processor::format::Format;
-pub use crate::processor::inputs::{FileInput, InputType, TextInput};
-pub use crate::processor::rename::Renamed;
+pub use crate::processor::inputs::{
+    DirectoryInput, FileInput, GlobInput, InputKind, InputReader, InputSeparator, InputType,
+    TextInput,
+};
+pub use crate::processor::normalize::{NormalizationForm, Normalizer};
+pub use crate::processor::rename::{Renamed, RenamedFile};
 pub use crate::processor::replacer::Replacer;
+pub use crate::processor::sanitize::Sanitizer;
 pub use crate::processor::selector::Selector;
-pub use crate::processor::trim::Trim;
+pub use crate::processor::sort::SortMode;
+pub use crate::processor::symlink::SymlinkPolicy;
+pub use crate::processor::trim::{Trim, TrimDirection, TrimType};
+pub use crate::transfer::{FileAction, Verification};
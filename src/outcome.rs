@@ -0,0 +1,161 @@
+use crate::{Error, Renamed};
+use std::fmt::{Display, Formatter};
+
+/// Represents what happened to a [`Renamed`] item when it was applied
+#[derive(Debug)]
+pub enum Outcome {
+    /// The new value is the same as the original so nothing was done
+    Unchanged,
+    /// The item was renamed
+    Renamed,
+    /// The item was not renamed for the provided reason
+    Skipped(String),
+    /// Renaming the item failed with the provided [`Error`]
+    Failed(Error),
+    /// The item was renamed but the symbolic link pointing at it could not be updated, see [`Error::LinkNotUpdated`]
+    LinkNotUpdated(Error),
+}
+
+/// Represents the [`Outcome`] of a single [`Renamed`] item
+#[derive(Debug)]
+pub struct ReportEntry {
+    original: String,
+    future: String,
+    outcome: Outcome,
+}
+
+/// Represents the [`Outcome`] of every [`Renamed`] item that was applied
+#[derive(Debug, Default)]
+pub struct Report {
+    entries: Vec<ReportEntry>,
+}
+
+/// Represents the number of items in a [`Report`] with each [`Outcome`]
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct Summary {
+    /// Number of [`Outcome::Unchanged`] items
+    pub unchanged: usize,
+    /// Number of [`Outcome::Renamed`] items
+    pub renamed: usize,
+    /// Number of [`Outcome::Skipped`] items
+    pub skipped: usize,
+    /// Number of [`Outcome::Failed`] items
+    pub failed: usize,
+    /// Number of [`Outcome::LinkNotUpdated`] items
+    pub link_not_updated: usize,
+}
+
+impl ReportEntry {
+    /// Create a new [`ReportEntry`] for the item
+    pub fn new(renamed: &dyn Renamed, outcome: Outcome) -> Self {
+        Self {
+            original: renamed.original().to_string(),
+            future: renamed.future().to_string(),
+            outcome,
+        }
+    }
+
+    /// Sets the new value when it differs from the item's, as when a [`ConflictPolicy`][crate::ConflictPolicy] adds a suffix
+    pub(crate) fn with_future(mut self, future: String) -> Self {
+        self.future = future;
+        self
+    }
+
+    /// Return the original value
+    pub fn original(&self) -> &str {
+        self.original.as_str()
+    }
+
+    /// Return the new value
+    pub fn future(&self) -> &str {
+        self.future.as_str()
+    }
+
+    /// Return the [`Outcome`]
+    pub fn outcome(&self) -> &Outcome {
+        &self.outcome
+    }
+}
+
+impl Report {
+    /// Apply every item on its own and report the [`Outcome`] of each, a failed item does not stop the others
+    ///
+    /// Files at the new path of an item are never replaced, the item is reported as [`Outcome::Failed`] instead.
+    pub fn apply(renamed: &[Box<dyn Renamed>]) -> Self {
+        renamed
+            .iter()
+            .map(|r| ReportEntry::new(r.as_ref(), r.apply()))
+            .collect()
+    }
+
+    /// Return the entries in the report
+    pub fn entries(&self) -> &[ReportEntry] {
+        self.entries.as_slice()
+    }
+
+    /// Return an iterator over the entries in the report
+    pub fn iter(&self) -> std::slice::Iter<'_, ReportEntry> {
+        self.entries.iter()
+    }
+
+    /// Return the number of items with each [`Outcome`]
+    pub fn summary(&self) -> Summary {
+        let mut summary = Summary::default();
+        for entry in self.entries.iter() {
+            match entry.outcome {
+                Outcome::Unchanged => summary.unchanged += 1,
+                Outcome::Renamed => summary.renamed += 1,
+                Outcome::Skipped(_) => summary.skipped += 1,
+                Outcome::Failed(_) => summary.failed += 1,
+                Outcome::LinkNotUpdated(_) => summary.link_not_updated += 1,
+            }
+        }
+        summary
+    }
+
+    /// Return `true` when no item failed and every symbolic link was updated
+    pub fn is_success(&self) -> bool {
+        let summary = self.summary();
+        summary.failed == 0 && summary.link_not_updated == 0
+    }
+}
+
+impl FromIterator<ReportEntry> for Report {
+    fn from_iter<T: IntoIterator<Item = ReportEntry>>(iter: T) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for Report {
+    type Item = ReportEntry;
+    type IntoIter = std::vec::IntoIter<ReportEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Report {
+    type Item = &'a ReportEntry;
+    type IntoIter = std::slice::Iter<'a, ReportEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} renamed, {} unchanged, {} skipped, {} failed",
+            self.renamed, self.unchanged, self.skipped, self.failed
+        )?;
+        match self.link_not_updated {
+            0 => Ok(()),
+            count => write!(f, ", {count} renamed without updating their link"),
+        }
+    }
+}
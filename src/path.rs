@@ -0,0 +1,18 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+// The path joined to the current directory when it is relative, or the path as is when that fails
+pub(crate) fn absolute_path(path: &Path) -> Cow<'_, Path> {
+    match std::path::absolute(path) {
+        Ok(absolute) => absolute.into(),
+        Err(_) => path.into(),
+    }
+}
+
+// Whether both paths refer to the same file, as on case-insensitive file systems when only the case is changed
+pub(crate) fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
@@ -1,31 +1,65 @@
+pub(crate) mod dedup;
 pub(crate) mod delimiter;
+pub(crate) mod dictionary;
+pub(crate) mod encoding;
 pub(crate) mod extractor;
+pub(crate) mod filter;
 pub(crate) mod format;
 pub(crate) mod inputs;
+pub(crate) mod normalize;
 pub(crate) mod rename;
 pub(crate) mod replacer;
+pub(crate) mod sanitize;
 pub(crate) mod selector;
+pub(crate) mod sort;
+pub(crate) mod symlink;
 pub(crate) mod trim;
 
-use crate::Error::InvalidValue;
+use crate::Error::{InvalidValue, NonUtf8FileName};
+use crate::processor::dedup::Deduplicate;
+use crate::processor::encoding::{NonUtf8Policy, escape_os_str};
+use crate::processor::filter::InputFilter;
 use crate::processor::inputs::InputType;
-use crate::processor::rename::{TextRenamer, filename_as_string_lossy, FileRenamer, RenameProcessor};
-use crate::{
-    Delimiter, Error, Extractor, Format, Renamed, Replacer, Selector,
-    Trim,
+use crate::processor::rename::{
+    FileRenamer, RenameProcessor, SkippedInput, TextRenamer, filename_as_string_lossy,
+    invalid_name_reason, process_format,
 };
-use indexmap::IndexSet;
+use crate::processor::sanitize::Sanitizer;
+use crate::processor::sort::SortMode;
+use crate::processor::symlink::{Link, SymlinkPolicy};
+use crate::transfer::FileAction;
+use crate::{Delimiter, Error, Extractor, Format, Normalizer, Renamed, Replacer, Selector, Trim};
+use indexmap::{IndexMap, IndexSet};
 use log::trace;
+use std::path::{Path, PathBuf};
+
+const EXTENSION_SEPARATOR: char = '.';
 
 /// A [`ProcessorBuilder`] is used to configure the renaming process and produces [`Renamed`] when processing is activated
 #[derive(Debug)]
 pub struct ProcessorBuilder {
+    compound_extensions: Vec<String>,
+    deduplicate: Deduplicate,
     delimiters: Vec<Delimiter>,
+    destination: Option<PathBuf>,
     extractors: Vec<Extractor>,
+    file_action: FileAction,
+    filters: Vec<InputFilter>,
+    follow_symlinks: bool,
     format: Format,
     inputs: IndexSet<InputType>,
+    non_utf8_policy: NonUtf8Policy,
+    normalizer: Option<Normalizer>,
+    output_normalizer: Option<Normalizer>,
+    input_replacers: Vec<Replacer>,
+    output_replacers: Vec<Replacer>,
     replacers: Vec<Replacer>,
+    reverse: bool,
+    sanitizer: Option<Sanitizer>,
     selectors: Vec<Selector>,
+    sort: SortMode,
+    stem_only: bool,
+    symlink_policy: SymlinkPolicy,
     trims: Vec<Trim>,
 }
 
@@ -33,16 +67,56 @@ impl ProcessorBuilder {
     /// Constructs new [`ProcessorBuilder`]
     pub fn new(format: Format) -> Self {
         Self {
+            compound_extensions: Vec::new(),
+            deduplicate: Deduplicate::default(),
             delimiters: Vec::new(),
+            destination: None,
             extractors: Vec::new(),
+            file_action: FileAction::default(),
+            filters: Vec::new(),
+            follow_symlinks: true,
             format,
             inputs: IndexSet::new(),
+            non_utf8_policy: NonUtf8Policy::default(),
+            normalizer: None,
+            output_normalizer: None,
+            input_replacers: Vec::new(),
+            output_replacers: Vec::new(),
             replacers: Vec::new(),
+            reverse: false,
+            sanitizer: None,
             selectors: Vec::new(),
+            sort: SortMode::default(),
+            stem_only: false,
+            symlink_policy: SymlinkPolicy::default(),
             trims: Vec::new(),
         }
     }
 
+    /// Appends a single extension made of multiple parts, such as `tar.gz`, that is kept whole when [`ProcessorBuilder::stem_only`] is set
+    pub fn compound_extension<S: AsRef<str>>(mut self, extension: S) -> Self {
+        self.compound_extensions.push(
+            extension
+                .as_ref()
+                .trim_start_matches(EXTENSION_SEPARATOR)
+                .into(),
+        );
+        self
+    }
+
+    /// Appends multiple extensions made of multiple parts that are kept whole when [`ProcessorBuilder::stem_only`] is set
+    pub fn compound_extensions<S: AsRef<str>>(self, extensions: Vec<S>) -> Self {
+        extensions
+            .iter()
+            .fold(self, |builder, e| builder.compound_extension(e))
+    }
+
+    /// Sets the [`Deduplicate`] mode used to remove file inputs referring to the same file
+    pub fn deduplicate(mut self, deduplicate: Deduplicate) -> Self {
+        self.deduplicate = deduplicate;
+        self
+    }
+
     /// Appends a single [`Delimiter`] item to the existing configuration
     pub fn delimiter(mut self, delimiter: Delimiter) -> Self {
         self.delimiters.push(delimiter);
@@ -67,6 +141,39 @@ impl ProcessorBuilder {
         self
     }
 
+    /// Sets the directory the new names of file inputs are relative to instead of the directory of each input
+    ///
+    /// New names are always relative and cannot contain `..`, a destination moves items into another directory tree or
+    /// onto another file system.
+    pub fn destination<P: AsRef<Path>>(mut self, destination: P) -> Self {
+        self.destination = Some(destination.as_ref().into());
+        self
+    }
+
+    /// Sets the [`FileAction`] taken at the new path of file inputs
+    pub fn file_action(mut self, file_action: FileAction) -> Self {
+        self.file_action = file_action;
+        self
+    }
+
+    /// Appends a single [`InputFilter`] item to the existing configuration, inputs must meet every filter to be processed
+    pub fn filter(mut self, filter: InputFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Appends multiple [`InputFilter`] items to the existing configuration, inputs must meet every filter to be processed
+    pub fn filters(mut self, filters: Vec<InputFilter>) -> Self {
+        self.filters.extend(filters);
+        self
+    }
+
+    /// Read the metadata used by filters and sorting from the target of symbolic links rather than the link itself, enabled by default
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
     /// Appends a single file path to the existing configuration
     pub fn input(mut self, input: InputType) -> Self {
         self.inputs.insert(input);
@@ -79,6 +186,30 @@ impl ProcessorBuilder {
         self
     }
 
+    /// Sets the [`NonUtf8Policy`] used for file names that are not valid UTF-8
+    pub fn non_utf8_policy(mut self, policy: NonUtf8Policy) -> Self {
+        self.non_utf8_policy = policy;
+        self
+    }
+
+    /// Sets the [`Normalizer`] that is applied to the input before input replacers, delimiters and extractors
+    pub fn normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.normalizer = Some(normalizer);
+        self
+    }
+
+    /// Sets the [`Normalizer`] that is applied to the output after the [`Format`] is rendered
+    pub fn output_normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.output_normalizer = Some(normalizer);
+        self
+    }
+
+    /// Sets the [`Sanitizer`] that cleans up the new name once the extension is added back
+    pub fn sanitizer(mut self, sanitizer: Sanitizer) -> Self {
+        self.sanitizer = Some(sanitizer);
+        self
+    }
+
     /// Appends a single [`Replacer`] item to the existing configuration
     pub fn replacer(mut self, trim: Replacer) -> Self {
         self.replacers.push(trim);
@@ -91,6 +222,44 @@ impl ProcessorBuilder {
         self
     }
 
+    /// Appends a single [`Replacer`] item that is applied to the whole input before it is split into segments
+    ///
+    /// Processing returns an error when the replacer is limited with [`Replacer::segments`] or [`Replacer::selector`].
+    pub fn input_replacer(mut self, replacer: Replacer) -> Self {
+        self.input_replacers.push(replacer);
+        self
+    }
+
+    /// Appends multiple [`Replacer`] items that are applied to the whole input before it is split into segments
+    ///
+    /// Processing returns an error when a replacer is limited with [`Replacer::segments`] or [`Replacer::selector`].
+    pub fn input_replacers(mut self, replacers: Vec<Replacer>) -> Self {
+        self.input_replacers.extend(replacers);
+        self
+    }
+
+    /// Appends a single [`Replacer`] item that is applied to the output after the [`Format`] is rendered
+    ///
+    /// Processing returns an error when the replacer is limited with [`Replacer::segments`] or [`Replacer::selector`].
+    pub fn output_replacer(mut self, replacer: Replacer) -> Self {
+        self.output_replacers.push(replacer);
+        self
+    }
+
+    /// Appends multiple [`Replacer`] items that are applied to the output after the [`Format`] is rendered
+    ///
+    /// Processing returns an error when a replacer is limited with [`Replacer::segments`] or [`Replacer::selector`].
+    pub fn output_replacers(mut self, replacers: Vec<Replacer>) -> Self {
+        self.output_replacers.extend(replacers);
+        self
+    }
+
+    /// Reverse the order inputs are processed in after sorting
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
     /// Appends a single [`Selector`] item to the existing configuration
     pub fn selector(mut self, selector: Selector) -> Self {
         self.selectors.push(selector);
@@ -103,6 +272,27 @@ impl ProcessorBuilder {
         self
     }
 
+    /// Sets the [`SortMode`] used to order inputs before [`ProcessorBuilder::process`] and [`ProcessorBuilder::process_subset`]
+    pub fn sort(mut self, sort: SortMode) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Process only the stem of each input and append the original extension to the rendered output
+    ///
+    /// The extension is the part after the last `.` when it only contains letters and digits, or the longest matching
+    /// compound extension. Directories, inputs starting with their only `.` and inputs such as `stuff.txt %e%` have no extension.
+    pub fn stem_only(mut self, stem_only: bool) -> Self {
+        self.stem_only = stem_only;
+        self
+    }
+
+    /// Sets the [`SymlinkPolicy`] used for file inputs that are symbolic links
+    pub fn symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlink_policy = policy;
+        self
+    }
+
     /// Appends a single [`Trim`] item to the existing configuration
     pub fn trim(mut self, trim: Trim) -> Self {
         self.trims.push(trim);
@@ -136,50 +326,209 @@ impl ProcessorBuilder {
         }
     }
 
+    /// Returns [`Renamed`] trait objects lazily for each of the provided inputs, such as those from an [`InputReader`][crate::InputReader]
+    ///
+    /// Inputs configured on the [`ProcessorBuilder`] are not included and inputs are not deduplicated.
+    pub fn process_iter<'a, I>(
+        &'a self,
+        inputs: I,
+    ) -> impl Iterator<Item = Result<Box<dyn Renamed>, Error>> + 'a
+    where
+        I: IntoIterator<Item = Result<InputType, Error>>,
+        I::IntoIter: 'a,
+    {
+        let (invalid, inputs) = match self.validate() {
+            Ok(_) => (None, Some(inputs.into_iter())),
+            Err(e) => (Some(Err(e)), None),
+        };
+        invalid
+            .into_iter()
+            .chain(inputs.into_iter().flatten().flat_map(move |input| {
+                match input.and_then(|i| i.expand_entries()) {
+                    Ok(expanded) => expanded
+                        .iter()
+                        .map(|(i, reason)| match reason {
+                            Some(reason) => Ok(Box::new(SkippedInput::new(input_name(i), reason))
+                                as Box<dyn Renamed>),
+                            None => self.process_input(i),
+                        })
+                        .collect::<Vec<_>>(),
+                    Err(e) => vec![Err(e)],
+                }
+            }))
+    }
+
+    // Segment targets only apply to segments so they are refused on replacers for the whole input or output
+    fn validate(&self) -> Result<(), Error> {
+        let targeted = self
+            .input_replacers
+            .iter()
+            .chain(self.output_replacers.iter())
+            .any(|r| r.is_targeted());
+        match targeted {
+            true => Err(InvalidValue(
+                "input and output replacers cannot be limited to segments or a selector"
+                    .to_string(),
+            )),
+            false => Ok(()),
+        }
+    }
+
     fn process_inputs(
         &self,
         processing_limit: Option<usize>,
     ) -> Result<Vec<Box<dyn Renamed>>, Error> {
+        self.validate()?;
         let mut renamed = Vec::new();
-        for input_type in self.inputs.iter() {
-            let process_string = match input_type {
-                InputType::File(i) => filename_as_string_lossy(i.value()),
-                InputType::Text(i) => i.value().into(),
-            };
-
-            let extracted = self.process_extractors(process_string.as_str());
-            let process_strings = vec![process_string];
-            let segments = self.process_delimiters(process_strings.as_slice());
-            let segments = self.process_trims(segments);
-            let segments = self.process_replacers(segments);
-            let selected = self.process_selectors(segments.as_slice());
-            renamed.push(match input_type {
-                InputType::File(i) => FileRenamer::new(
-                    i.value(),
-                    segments,
-                    selected,
-                    extracted,
-                    self.format.clone(),
-                )
-                .rename(),
-                InputType::Text(i) => TextRenamer::new(
-                    i.value(),
-                    segments,
-                    selected,
-                    extracted,
-                    self.format.clone(),
-                )
-                .rename(),
+        for (input_type, skip_reason) in self.expand_inputs()? {
+            renamed.push(match skip_reason {
+                Some(reason) => Box::new(SkippedInput::new(input_name(&input_type), reason)),
+                None => self.process_input(&input_type)?,
             });
-            if let Some(limit) = processing_limit {
-                if renamed.len() == limit {
-                    break;
-                }
+            if let Some(limit) = processing_limit
+                && renamed.len() == limit
+            {
+                break;
             }
         }
         Ok(renamed)
     }
 
+    fn expand_inputs(&self) -> Result<Vec<(InputType, Option<String>)>, Error> {
+        let mut expanded = IndexMap::new();
+        for input_type in self.inputs.iter() {
+            for (input, reason) in input_type.expand_entries()? {
+                expanded.entry(input).or_insert(reason);
+            }
+        }
+        let sorted = self.sort.sort(
+            expanded.into_iter().collect(),
+            self.extractors.as_slice(),
+            self.reverse,
+            self.follow_symlinks,
+        );
+        Ok(self.deduplicate.deduplicate(sorted))
+    }
+
+    fn process_input(&self, input_type: &InputType) -> Result<Box<dyn Renamed>, Error> {
+        for filter in self.filters.as_slice() {
+            if let Some(reason) = filter.reject(input_type, self.follow_symlinks) {
+                return Ok(Box::new(SkippedInput::new(input_name(input_type), reason)));
+            }
+        }
+
+        let link = match input_type {
+            InputType::File(i) if i.value().is_symlink() => match self.symlink_policy {
+                SymlinkPolicy::Link => None,
+                SymlinkPolicy::Target => Some(Link::read(i.value())?),
+                SymlinkPolicy::Skip => {
+                    return Ok(Box::new(SkippedInput::new(
+                        filename_as_string_lossy(i.value()),
+                        "input is a symbolic link",
+                    )));
+                }
+            },
+            _ => None,
+        };
+        let target;
+        let input_type = match &link {
+            Some(link) => {
+                target = InputType::new_file(link.resolved_target());
+                &target
+            }
+            None => input_type,
+        };
+
+        let escaped = matches!(input_type, InputType::File(i) if i.value().file_name().is_some_and(|n| n.to_str().is_none()));
+        let process_string = match input_type {
+            InputType::File(i) if escaped => match self.non_utf8_policy {
+                NonUtf8Policy::Escape => escape_os_str(i.value().file_name().unwrap_or_default()),
+                NonUtf8Policy::Skip => {
+                    return Ok(Box::new(SkippedInput::new(
+                        filename_as_string_lossy(i.value()),
+                        "file name is not valid UTF-8",
+                    )));
+                }
+                NonUtf8Policy::Error => return Err(NonUtf8FileName(i.value().into())),
+            },
+            InputType::File(i) => filename_as_string_lossy(i.value()),
+            InputType::Text(i) => i.value().into(),
+            InputType::Directory(_) | InputType::Glob(_) => return Err(unexpanded(input_type)),
+        };
+
+        let process_string = self.process_normalizer(process_string);
+        let extracted = self.process_extractors(process_string.as_str());
+        let is_dir = matches!(input_type, InputType::File(i) if i.value().is_dir());
+        let (stem, extension) = match is_dir {
+            true => (process_string, None),
+            false => self.split_extension(process_string),
+        };
+        let process_strings = vec![self.process_input_replacers(stem)];
+        let segments = self.process_delimiters(process_strings.as_slice());
+        let segments = self.process_trims(segments);
+        let segments = self.process_replacers(segments);
+        let selected = self.process_selectors(segments.as_slice());
+        let new_name = process_format(
+            segments.as_slice(),
+            selected.as_slice(),
+            extracted.as_slice(),
+            &self.format,
+        );
+        let new_name = self.process_output_normalizer(new_name);
+        let new_name = self.process_output_replacers(new_name);
+        let new_name = match extension {
+            Some(extension) => format!("{new_name}{EXTENSION_SEPARATOR}{extension}"),
+            None => new_name,
+        };
+        let new_name = self.process_sanitizer(new_name, escaped);
+        if let InputType::File(i) = input_type
+            && let Some(reason) = invalid_name_reason(new_name.as_str())
+        {
+            return Ok(Box::new(SkippedInput::new(
+                filename_as_string_lossy(i.value()),
+                reason,
+            )));
+        }
+        Ok(match input_type {
+            InputType::File(i) if escaped => FileRenamer::new_escaped(i.value(), new_name)
+                .destination(self.destination.clone())
+                .file_action(self.file_action)
+                .link(link)
+                .rename(),
+            InputType::File(i) => FileRenamer::new(i.value(), new_name)
+                .destination(self.destination.clone())
+                .file_action(self.file_action)
+                .link(link)
+                .rename(),
+            InputType::Text(i) => TextRenamer::new(i.value(), new_name.as_str()).rename(),
+            InputType::Directory(_) | InputType::Glob(_) => return Err(unexpanded(input_type)),
+        })
+    }
+
+    fn split_extension(&self, value: String) -> (String, Option<String>) {
+        if !self.stem_only {
+            return (value, None);
+        }
+        let compound = self
+            .compound_extensions
+            .iter()
+            .filter(|e| value.len() > e.len() + 1)
+            .filter(|e| value.is_char_boundary(value.len() - e.len() - 1))
+            .filter(|e| {
+                let suffix = &value[value.len() - e.len() - 1..];
+                suffix.starts_with(EXTENSION_SEPARATOR) && suffix[1..].eq_ignore_ascii_case(e)
+            })
+            .map(|e| value.len() - e.len() - 1)
+            .min();
+        let simple = value
+            .rfind(EXTENSION_SEPARATOR)
+            .filter(|i| *i > 0 && is_extension(&value[i + 1..]));
+        match compound.or(simple) {
+            Some(i) => (value[..i].to_string(), Some(value[i + 1..].to_string())),
+            None => (value, None),
+        }
+    }
+
     fn process_delimiters<S: AsRef<str>>(&self, value: &[S]) -> Vec<String> {
         let mut output = Vec::new();
         for delimiter in &self.delimiters {
@@ -210,6 +559,28 @@ impl ProcessorBuilder {
             .collect()
     }
 
+    fn process_normalizer(&self, value: String) -> String {
+        match &self.normalizer {
+            None => value,
+            Some(n) => n.normalize(value),
+        }
+    }
+
+    fn process_output_normalizer(&self, value: String) -> String {
+        match &self.output_normalizer {
+            None => value,
+            Some(n) => n.normalize(value),
+        }
+    }
+
+    fn process_sanitizer(&self, value: String, escaped: bool) -> String {
+        match &self.sanitizer {
+            None => value,
+            Some(s) if escaped => s.sanitize_escaped(value),
+            Some(s) => s.sanitize(value),
+        }
+    }
+
     fn process_trims(&self, segments: Vec<String>) -> Vec<String> {
         let mut output = segments;
         for t in self.trims.as_slice() {
@@ -225,4 +596,43 @@ impl ProcessorBuilder {
         }
         output
     }
+
+    fn process_input_replacers(&self, value: String) -> String {
+        let mut output = value;
+        for r in self.input_replacers.as_slice() {
+            output = r.replace(output)
+        }
+        output
+    }
+
+    fn process_output_replacers(&self, value: String) -> String {
+        let mut output = value;
+        for r in self.output_replacers.as_slice() {
+            output = r.replace(output)
+        }
+        output
+    }
+}
+
+// File extensions are made of letters and digits, anything else after the last `.` is part of the stem
+fn is_extension(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(char::is_alphanumeric)
+}
+
+// Name of an input as shown in the original value of skipped inputs
+fn input_name(input_type: &InputType) -> String {
+    match input_type {
+        InputType::File(i) => filename_as_string_lossy(i.value()),
+        InputType::Text(i) => i.value().into(),
+        InputType::Directory(i) => i.value().to_string_lossy().into(),
+        InputType::Glob(i) => i.value().into(),
+    }
+}
+
+// Directory and glob inputs are expanded into file inputs before they are processed
+fn unexpanded(input_type: &InputType) -> Error {
+    InvalidValue(format!(
+        "{} must be expanded before it is processed",
+        input_name(input_type)
+    ))
 }
@@ -0,0 +1,88 @@
+use crate::processor::inputs::InputType;
+use log::debug;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Used with the [`ProcessorBuilder`][crate::ProcessorBuilder] to choose how file inputs referring to the same file are detected
+///
+/// The first input for a file is kept and later duplicates are reported as skipped.
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum Deduplicate {
+    /// Only inputs with identical paths are duplicates
+    #[default]
+    None,
+    /// Inputs with the same canonical path are duplicates
+    CanonicalPath,
+    /// Inputs with the same canonical path are duplicates and inputs on the same device with the same inode are
+    /// reported as skipped hard links
+    ///
+    /// Only supported on Unix, other platforms behave as [`Deduplicate::CanonicalPath`].
+    FileId,
+}
+
+impl Deduplicate {
+    /// Pair each input with the reason it is skipped as a duplicate, if it is, inputs already skipped are kept as they are
+    pub(crate) fn deduplicate(
+        &self,
+        inputs: Vec<(InputType, Option<String>)>,
+    ) -> Vec<(InputType, Option<String>)> {
+        if *self == Deduplicate::None {
+            return inputs;
+        }
+
+        let mut paths: HashMap<_, PathBuf> = HashMap::new();
+        let mut ids: HashMap<_, PathBuf> = HashMap::new();
+        let mut output = Vec::with_capacity(inputs.len());
+        for (input, skip_reason) in inputs {
+            let path = match &input {
+                InputType::File(i) if skip_reason.is_none() => i.value().to_path_buf(),
+                _ => {
+                    output.push((input, skip_reason));
+                    continue;
+                }
+            };
+            let canonical = canonical_path(path.as_path());
+            if let Some(first) = paths.get(&canonical) {
+                debug!("Skipping {path:?} as a duplicate of {first:?}");
+                let reason = format!("input is a duplicate of {}", first.display());
+                output.push((input, Some(reason)));
+                continue;
+            }
+            let reason = match self {
+                Deduplicate::FileId => file_id(path.as_path()).and_then(|id| match ids.get(&id) {
+                    Some(first) => Some(format!("input is a hard link to {}", first.display())),
+                    None => {
+                        ids.insert(id, path.clone());
+                        None
+                    }
+                }),
+                _ => None,
+            };
+            paths.insert(canonical, path);
+            output.push((input, reason));
+        }
+        output
+    }
+}
+
+// Canonicalize the parent directory only so a symbolic link is not resolved to its target
+fn canonical_path(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    match (absolute.parent(), absolute.file_name()) {
+        (Some(parent), Some(name)) => std::fs::canonicalize(parent)
+            .map(|p| p.join(name))
+            .unwrap_or(absolute),
+        _ => absolute,
+    }
+}
+
+#[cfg(unix)]
+fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    path.symlink_metadata().ok().map(|m| (m.dev(), m.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
@@ -0,0 +1,160 @@
+use crate::Error::InvalidValue;
+use crate::error::Error;
+use aho_corasick::{AhoCorasick, MatchKind};
+use indexmap::IndexMap;
+use std::cmp::Reverse;
+use std::path::Path;
+
+const CSV_EXTENSION: &str = "csv";
+const COMMENT_PREFIX: char = '#';
+
+/// Used with a dictionary [`Replacer`][crate::Replacer] to indicate how the mappings are matched
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum DictionaryMatch {
+    /// Mappings are matched against whole words within a value
+    WholeWord,
+    /// Mappings are matched against the entire value, which counts as a single replacement towards the limit
+    WholeSegment,
+}
+
+/// Represents a table of mappings used to replace whole words or values
+#[derive(Debug, Clone)]
+pub(crate) struct Dictionary {
+    match_type: DictionaryMatch,
+    mappings: IndexMap<String, String>,
+    matcher: AhoCorasick,
+}
+
+impl Dictionary {
+    /// Create a new [`Dictionary`] from pairs of original and substitute values
+    pub(crate) fn new<S: AsRef<str>>(
+        mappings: Vec<(S, S)>,
+        match_type: DictionaryMatch,
+    ) -> Result<Self, Error> {
+        let mappings: IndexMap<String, String> = mappings
+            .iter()
+            .filter(|(k, _)| !k.as_ref().is_empty())
+            .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
+            .collect();
+        let matcher = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(mappings.keys())?;
+        Ok(Self {
+            match_type,
+            mappings,
+            matcher,
+        })
+    }
+
+    /// Create a new [`Dictionary`] from a TSV file, or a CSV file when the extension is `csv`
+    pub(crate) fn from_file<P: AsRef<Path>>(
+        path: P,
+        match_type: DictionaryMatch,
+    ) -> Result<Self, Error> {
+        let is_csv = path
+            .as_ref()
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case(CSV_EXTENSION));
+        let contents = std::fs::read_to_string(path.as_ref())?;
+
+        let mut mappings = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with(COMMENT_PREFIX) {
+                continue;
+            }
+            let fields = match is_csv {
+                true => split_csv_line(line),
+                false => line.split('\t').map(|f| f.to_string()).collect(),
+            };
+            match fields.as_slice() {
+                [original, substitute] => mappings.push((original.clone(), substitute.clone())),
+                _ => {
+                    return Err(InvalidValue(format!(
+                        "{} line {} must contain exactly 2 fields",
+                        path.as_ref().display(),
+                        number + 1
+                    )));
+                }
+            }
+        }
+        Self::new(mappings, match_type)
+    }
+
+    /// Replace matching values up to the limit provided, 0 replaces all matches
+    pub(crate) fn replace(&self, value: &str, limit: usize) -> String {
+        match self.match_type {
+            DictionaryMatch::WholeSegment => self.replace_segment(value),
+            DictionaryMatch::WholeWord => self.replace_words(value, limit),
+        }
+    }
+
+    // The whole value is a single match, every limit allows at least one replacement so it always applies
+    fn replace_segment(&self, value: &str) -> String {
+        match self.mappings.get(value) {
+            Some(substitute) => substitute.to_string(),
+            None => value.to_string(),
+        }
+    }
+
+    fn replace_words(&self, value: &str, limit: usize) -> String {
+        let mut matches: Vec<_> = self
+            .matcher
+            .find_overlapping_iter(value)
+            .filter(|m| is_whole_word(value, m.start(), m.end()))
+            .collect();
+        matches.sort_by_key(|m| (m.start(), Reverse(m.end())));
+
+        let mut output = String::with_capacity(value.len());
+        let mut position = 0;
+        let mut count = 0;
+        for m in matches {
+            if m.start() < position {
+                continue;
+            }
+            if limit != 0 && count == limit {
+                break;
+            }
+            output.push_str(&value[position..m.start()]);
+            output.push_str(self.mappings[m.pattern().as_usize()].as_str());
+            position = m.end();
+            count += 1;
+        }
+        output.push_str(&value[position..]);
+        output
+    }
+}
+
+// Word characters at the edges of a match must not continue into the surrounding text
+fn is_whole_word(value: &str, start: usize, end: usize) -> bool {
+    let matched = &value[start..end];
+    let starts_word = matched.chars().next().is_some_and(char::is_alphanumeric);
+    let ends_word = matched
+        .chars()
+        .next_back()
+        .is_some_and(char::is_alphanumeric);
+    let before = value[..start].chars().next_back();
+    let after = value[end..].chars().next();
+
+    !(starts_word && before.is_some_and(char::is_alphanumeric)
+        || ends_word && after.is_some_and(char::is_alphanumeric))
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
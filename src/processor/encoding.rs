@@ -0,0 +1,170 @@
+use EscapedUnit::{Char, Escaped};
+use std::ffi::{OsStr, OsString};
+
+const ESCAPE_CHAR: char = '\\';
+
+/// Used with the [`ProcessorBuilder`][crate::ProcessorBuilder] to choose how file names that are not valid UTF-8 are processed
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum NonUtf8Policy {
+    /// Process the file name with invalid values escaped and restore the original values in the new file name
+    ///
+    /// Invalid bytes are escaped as `\xHH` on Unix and unpaired surrogates as `\uHHHH` on Windows.
+    /// A `\` already in the file name is escaped as `\\`.
+    #[default]
+    Escape,
+    /// Do not process the file and report it as skipped
+    Skip,
+    /// Stop processing and return an [`Error`][crate::Error]
+    Error,
+}
+
+/// Escape a value that is not valid UTF-8 so it can be processed as a [`String`]
+pub(crate) fn escape_os_str(value: &OsStr) -> String {
+    let mut output = String::new();
+    escape_platform(value, &mut output);
+    output
+}
+
+/// Reverse [`escape_os_str`] on a processed value
+pub(crate) fn unescape_os_str<S: AsRef<str>>(value: S) -> OsString {
+    let mut units = Vec::new();
+    let mut chars = value.as_ref().chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ESCAPE_CHAR {
+            match chars.peek() {
+                Some(&ESCAPE_CHAR) => {
+                    chars.next();
+                }
+                Some(&marker) if marker == UNIT_MARKER => {
+                    let digits: String = chars.clone().skip(1).take(UNIT_DIGITS).collect();
+                    if digits.len() == UNIT_DIGITS
+                        && digits.chars().all(|d| d.is_ascii_hexdigit())
+                        && let Ok(unit) = Unit::from_str_radix(digits.as_str(), 16)
+                    {
+                        chars.nth(UNIT_DIGITS);
+                        units.push(Escaped(unit));
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+        }
+        units.push(Char(c));
+    }
+    from_units(units)
+}
+
+/// Return the length in bytes of the escaped unit the value starts with, if it starts with one
+pub(crate) fn escaped_unit_len(value: &str) -> Option<usize> {
+    let digits = value
+        .strip_prefix(ESCAPE_CHAR)?
+        .strip_prefix(UNIT_MARKER)?
+        .get(..UNIT_DIGITS)?;
+    digits
+        .chars()
+        .all(|d| d.is_ascii_hexdigit())
+        .then_some(ESCAPE_CHAR.len_utf8() + UNIT_MARKER.len_utf8() + UNIT_DIGITS)
+}
+
+enum EscapedUnit {
+    Char(char),
+    Escaped(Unit),
+}
+
+fn push_char(c: char, output: &mut String) {
+    if c == ESCAPE_CHAR {
+        output.push(ESCAPE_CHAR);
+    }
+    output.push(c);
+}
+
+#[cfg(unix)]
+type Unit = u8;
+#[cfg(unix)]
+const UNIT_MARKER: char = 'x';
+#[cfg(unix)]
+const UNIT_DIGITS: usize = 2;
+
+#[cfg(unix)]
+fn escape_platform(value: &OsStr, output: &mut String) {
+    use std::os::unix::ffi::OsStrExt;
+    for chunk in value.as_bytes().utf8_chunks() {
+        chunk.valid().chars().for_each(|c| push_char(c, output));
+        for byte in chunk.invalid() {
+            output.push_str(format!("{ESCAPE_CHAR}{UNIT_MARKER}{byte:02X}").as_str());
+        }
+    }
+}
+
+#[cfg(unix)]
+fn from_units(units: Vec<EscapedUnit>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    let mut bytes = Vec::new();
+    for unit in units {
+        match unit {
+            Char(c) => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            Escaped(b) => bytes.push(b),
+        }
+    }
+    OsString::from_vec(bytes)
+}
+
+#[cfg(windows)]
+type Unit = u16;
+#[cfg(windows)]
+const UNIT_MARKER: char = 'u';
+#[cfg(windows)]
+const UNIT_DIGITS: usize = 4;
+
+#[cfg(windows)]
+fn escape_platform(value: &OsStr, output: &mut String) {
+    use std::os::windows::ffi::OsStrExt;
+    for c in char::decode_utf16(value.encode_wide()) {
+        match c {
+            Ok(c) => push_char(c, output),
+            Err(e) => output.push_str(
+                format!("{ESCAPE_CHAR}{UNIT_MARKER}{:04X}", e.unpaired_surrogate()).as_str(),
+            ),
+        }
+    }
+}
+
+#[cfg(windows)]
+fn from_units(units: Vec<EscapedUnit>) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    let mut wide = Vec::new();
+    for unit in units {
+        match unit {
+            Char(c) => wide.extend_from_slice(c.encode_utf16(&mut [0; 2])),
+            Escaped(w) => wide.push(w),
+        }
+    }
+    OsString::from_wide(wide.as_slice())
+}
+
+#[cfg(not(any(unix, windows)))]
+type Unit = u8;
+#[cfg(not(any(unix, windows)))]
+const UNIT_MARKER: char = 'x';
+#[cfg(not(any(unix, windows)))]
+const UNIT_DIGITS: usize = 2;
+
+#[cfg(not(any(unix, windows)))]
+fn escape_platform(value: &OsStr, output: &mut String) {
+    value
+        .to_string_lossy()
+        .chars()
+        .for_each(|c| push_char(c, output));
+}
+
+#[cfg(not(any(unix, windows)))]
+fn from_units(units: Vec<EscapedUnit>) -> OsString {
+    units
+        .into_iter()
+        .map(|unit| match unit {
+            Char(c) => c,
+            Escaped(b) => char::from(b),
+        })
+        .collect::<String>()
+        .into()
+}
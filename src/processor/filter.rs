@@ -0,0 +1,143 @@
+use crate::processor::inputs::InputType;
+use crate::processor::rename::filename_as_string_lossy;
+use crate::processor::symlink::metadata;
+use regex::Regex;
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::SystemTime;
+
+const HIDDEN_PREFIX: char = '.';
+const EXTENSION_SEPARATOR: char = '.';
+
+/// Used with [`InputFilter::FileType`] to indicate the kind of file system entry
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum FileKind {
+    /// A regular file
+    File,
+    /// A directory
+    Directory,
+    /// A symbolic link, which is not followed
+    Symlink,
+}
+
+/// Represents a condition an input must meet to be processed
+///
+/// Inputs that do not meet the condition are reported as skipped. Conditions that need file metadata only apply to file inputs.
+#[derive(Debug, Clone)]
+pub enum InputFilter {
+    /// Only process file system entries of the provided [`FileKind`]
+    FileType(FileKind),
+    /// Only process inputs starting with `.` when `true`, or inputs not starting with `.` when `false`
+    Hidden(bool),
+    /// Only process files with a size in bytes within the inclusive range
+    Size {
+        /// Smallest size allowed
+        min: Option<u64>,
+        /// Largest size allowed
+        max: Option<u64>,
+    },
+    /// Only process files last modified within the inclusive range
+    Modified {
+        /// Earliest modification time allowed
+        after: Option<SystemTime>,
+        /// Latest modification time allowed
+        before: Option<SystemTime>,
+    },
+    /// Only process inputs with one of the provided extensions, compared ignoring ASCII case
+    Extensions(Vec<String>),
+    /// Only process inputs whose name matches the provided [`Regex`]
+    Name(Regex),
+}
+
+impl InputFilter {
+    /// Returns the reason the input does not meet the condition, or [`None`] when it does
+    ///
+    /// Size and modification time are read from the target of symbolic links when `follow_symlinks` is set. An input whose
+    /// metadata cannot be read does not meet conditions that need it.
+    pub fn reject(&self, input: &InputType, follow_symlinks: bool) -> Option<String> {
+        let (name, path) = match input {
+            InputType::File(i) => (filename_as_string_lossy(i.value()), Some(i.value())),
+            InputType::Text(i) => (i.value().to_string(), None),
+            InputType::Directory(_) | InputType::Glob(_) => return None,
+        };
+
+        match self {
+            InputFilter::FileType(kind) => match path.map(|p| p.symlink_metadata()) {
+                Some(Err(error)) => Some(unreadable(error)),
+                Some(Ok(metadata)) if file_kind(&metadata) != Some(*kind) => {
+                    Some(format!("input is not a {}", kind_name(*kind)))
+                }
+                _ => None,
+            },
+            InputFilter::Hidden(hidden) => match (name.starts_with(HIDDEN_PREFIX), hidden) {
+                (true, false) => Some("input is hidden".to_string()),
+                (false, true) => Some("input is not hidden".to_string()),
+                _ => None,
+            },
+            InputFilter::Size { min, max } => match path.map(|p| metadata(p, follow_symlinks)) {
+                Some(Err(error)) => Some(unreadable(error)),
+                Some(Ok(metadata)) => {
+                    let size = metadata.len();
+                    match (min, max) {
+                        (Some(min), _) if size < *min => Some(format!(
+                            "file size of {size} bytes is below the minimum of {min} bytes"
+                        )),
+                        (_, Some(max)) if size > *max => Some(format!(
+                            "file size of {size} bytes is above the maximum of {max} bytes"
+                        )),
+                        _ => None,
+                    }
+                }
+                None => None,
+            },
+            InputFilter::Modified { after, before } => {
+                match path.map(|p| metadata(p, follow_symlinks).and_then(|m| m.modified())) {
+                    Some(Err(error)) => Some(unreadable(error)),
+                    Some(Ok(modified)) => match (after, before) {
+                        (Some(after), _) if modified < *after => {
+                            Some("file was modified before the earliest time allowed".to_string())
+                        }
+                        (_, Some(before)) if modified > *before => {
+                            Some("file was modified after the latest time allowed".to_string())
+                        }
+                        _ => None,
+                    },
+                    None => None,
+                }
+            }
+            InputFilter::Extensions(extensions) => {
+                let extension = Path::new(name.as_str()).extension();
+                let accepted = extension.is_some_and(|extension| {
+                    extensions.iter().any(|e| {
+                        extension.eq_ignore_ascii_case(e.trim_start_matches(EXTENSION_SEPARATOR))
+                    })
+                });
+                (!accepted).then(|| format!("extension is not one of {}", extensions.join(", ")))
+            }
+            InputFilter::Name(pattern) => (!pattern.is_match(name.as_str()))
+                .then(|| format!("name does not match the pattern {}", pattern.as_str())),
+        }
+    }
+}
+
+fn unreadable(error: std::io::Error) -> String {
+    format!("metadata could not be read: {error}")
+}
+
+fn kind_name(kind: FileKind) -> &'static str {
+    match kind {
+        FileKind::File => "regular file",
+        FileKind::Directory => "directory",
+        FileKind::Symlink => "symbolic link",
+    }
+}
+
+fn file_kind(metadata: &Metadata) -> Option<FileKind> {
+    let file_type = metadata.file_type();
+    match file_type {
+        t if t.is_symlink() => Some(FileKind::Symlink),
+        t if t.is_dir() => Some(FileKind::Directory),
+        t if t.is_file() => Some(FileKind::File),
+        _ => None,
+    }
+}
@@ -1,28 +1,99 @@
+use crate::Error::{InvalidValue, NoGlobMatches};
+use crate::error::Error;
+use globset::{Glob, GlobBuilder, GlobSet};
+use log::warn;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::io::BufRead;
 use std::path::{Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
+
+const HIDDEN_PREFIX: &str = ".";
+const CARRIAGE_RETURN: u8 = b'\r';
+const HOME_PREFIX: &str = "~";
+const GLOB_SEPARATOR: char = '/';
+const GLOB_META_CHARS: [char; 4] = ['*', '?', '[', '{'];
+const RECURSIVE_GLOB: &str = "**";
 
 /// Enum used to pass inputs to the [`ProcessorBuilder`][crate::ProcessorBuilder]
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum InputType {
     /// Represents file path input and contains [`FileInput`]
     File(FileInput),
     /// Represents plain text input and contains [`TextInput`]
     Text(TextInput),
+    /// Represents a directory that is expanded into file path inputs and contains [`DirectoryInput`]
+    Directory(DirectoryInput),
+    /// Represents a glob pattern that is expanded into file path inputs and contains [`GlobInput`]
+    Glob(GlobInput),
+}
+
+/// Used with [`InputReader`] to indicate how entries in the stream are separated
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum InputSeparator {
+    /// Entries are separated by `\n`, a trailing `\r` is removed
+    Newline,
+    /// Entries are separated by NUL as produced by `find -print0`
+    Nul,
+}
+
+/// Used with [`InputReader`] to indicate which [`InputType`] variant is produced for each entry
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum InputKind {
+    /// Produce [`InputType::File`]
+    File,
+    /// Produce [`InputType::Text`]
+    Text,
+}
+
+/// An [`Iterator`] that reads [`InputType`] items one at a time from a [`BufRead`]
+///
+/// Empty entries are ignored.
+#[derive(Debug)]
+pub struct InputReader<R: BufRead> {
+    reader: R,
+    separator: InputSeparator,
+    kind: InputKind,
 }
 
 /// Represents file path input in [`InputType`]
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct FileInput {
     value: PathBuf,
 }
 
 /// Represents plain text input in [`InputType`]
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct TextInput {
     value: String,
 }
 
+/// Represents a glob pattern in [`InputType`] that is expanded into [`FileInput`] items
+///
+/// Patterns use `/` as the separator and support `*`, `?`, `**`, character classes like `[0-9]`
+/// and brace expansion like `{mkv,mp4}`. A leading `~` is replaced with the home directory.
+/// Hidden entries, those starting with `.`, are skipped unless [`GlobInput::include_hidden`] is set.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct GlobInput {
+    value: String,
+    include_hidden: bool,
+}
+
+/// Represents a directory in [`InputType`] and how its contents are expanded into [`FileInput`] items
+///
+/// Entries are visited in file name order. Hidden entries, those starting with `.`, are skipped unless
+/// [`DirectoryInput::include_hidden`] is set and skipped directories are not descended into.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DirectoryInput {
+    value: PathBuf,
+    recursive: bool,
+    max_depth: Option<usize>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    include_directories: bool,
+    include_hidden: bool,
+}
+
 impl InputType {
     /// Create a new [`InputType`] of variant [`File`]
     pub fn new_file<P: AsRef<Path>>(value: P) -> Self {
@@ -33,6 +104,41 @@ impl InputType {
     pub fn new_text<S: AsRef<str>>(value: S) -> Self {
         Self::Text(TextInput::new(value))
     }
+
+    /// Create a new [`InputType`] of variant [`Directory`] that expands to the files directly inside it
+    pub fn new_directory<P: AsRef<Path>>(value: P) -> Self {
+        Self::Directory(DirectoryInput::new(value))
+    }
+
+    /// Create a new [`InputType`] of variant [`Glob`]
+    pub fn new_glob<S: AsRef<str>>(value: S) -> Self {
+        Self::Glob(GlobInput::new(value))
+    }
+
+    /// Create an [`InputReader`] that reads [`InputType`] items from the provided reader
+    pub fn from_reader<R: BufRead>(
+        reader: R,
+        separator: InputSeparator,
+        kind: InputKind,
+    ) -> InputReader<R> {
+        InputReader::new(reader, separator, kind)
+    }
+
+    /// Expand into the [`File`] and [`Text`] inputs this [`InputType`] represents
+    ///
+    /// Paths that could not be read while walking the file system are logged and left out.
+    pub fn expand(&self) -> Result<Vec<InputType>, Error> {
+        Ok(readable(self.expand_entries()?))
+    }
+
+    /// Expand into the [`File`] and [`Text`] inputs paired with the reason a path could not be read, if it could not
+    pub(crate) fn expand_entries(&self) -> Result<Vec<(InputType, Option<String>)>, Error> {
+        match self {
+            InputType::File(_) | InputType::Text(_) => Ok(vec![(self.clone(), None)]),
+            InputType::Directory(i) => i.expand_entries(),
+            InputType::Glob(i) => i.expand_entries(),
+        }
+    }
 }
 
 impl FileInput {
@@ -62,3 +168,312 @@ impl TextInput {
         &self.value
     }
 }
+
+impl<R: BufRead> InputReader<R> {
+    /// Create a new [`InputReader`]
+    pub fn new(reader: R, separator: InputSeparator, kind: InputKind) -> Self {
+        Self {
+            reader,
+            separator,
+            kind,
+        }
+    }
+
+    fn read_entry(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        let delimiter = match self.separator {
+            InputSeparator::Newline => b'\n',
+            InputSeparator::Nul => b'\0',
+        };
+        let mut entry = Vec::new();
+        while entry.is_empty() {
+            if self.reader.read_until(delimiter, &mut entry)? == 0 {
+                return Ok(None);
+            }
+            if entry.last() == Some(&delimiter) {
+                entry.pop();
+            }
+            if self.separator == InputSeparator::Newline && entry.last() == Some(&CARRIAGE_RETURN) {
+                entry.pop();
+            }
+        }
+        Ok(Some(entry))
+    }
+
+    fn to_input(&self, entry: Vec<u8>) -> Result<InputType, Error> {
+        match self.kind {
+            InputKind::File => Ok(InputType::new_file(path_from_bytes(entry)?)),
+            InputKind::Text => Ok(InputType::new_text(
+                String::from_utf8(entry).map_err(|e| InvalidValue(e.to_string()))?,
+            )),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for InputReader<R> {
+    type Item = Result<InputType, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_entry()
+            .transpose()
+            .map(|entry| entry.and_then(|e| self.to_input(e)))
+    }
+}
+
+impl GlobInput {
+    /// Create a new [`GlobInput`]
+    pub fn new<S: AsRef<str>>(value: S) -> Self {
+        Self {
+            value: value.as_ref().into(),
+            include_hidden: false,
+        }
+    }
+
+    /// Include entries starting with `.`
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /// Return value as a [`str`]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Walk the file system and return a [`FileInput`] for each path matching the pattern
+    ///
+    /// Paths that could not be read are logged and left out.
+    pub fn expand(&self) -> Result<Vec<InputType>, Error> {
+        Ok(readable(self.expand_entries()?))
+    }
+
+    fn expand_entries(&self) -> Result<Vec<(InputType, Option<String>)>, Error> {
+        let pattern = expand_home(self.value());
+        let components: Vec<_> = pattern.split(GLOB_SEPARATOR).collect();
+        let literal_count = components
+            .iter()
+            .take_while(|c| !c.contains(GLOB_META_CHARS))
+            .count();
+
+        let mut inputs = Vec::new();
+        if literal_count == components.len() {
+            if Path::new(pattern.as_str()).exists() {
+                inputs.push((InputType::new_file(pattern.as_str()), None));
+            }
+        } else {
+            let separator = GLOB_SEPARATOR.to_string();
+            let base = match components[..literal_count].join(separator.as_str()) {
+                b if b.is_empty() && literal_count > 0 => separator.clone(),
+                b => b,
+            };
+            let remainder = &components[literal_count..];
+            let matcher = GlobBuilder::new(remainder.join(separator.as_str()).as_str())
+                .literal_separator(true)
+                .build()?
+                .compile_matcher();
+            let max_depth = match remainder.iter().any(|c| c.contains(RECURSIVE_GLOB)) {
+                true => usize::MAX,
+                false => remainder.len(),
+            };
+            let root = match base.is_empty() {
+                true => Path::new("."),
+                false => Path::new(base.as_str()),
+            };
+
+            let walker = WalkDir::new(root)
+                .min_depth(1)
+                .max_depth(max_depth)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|e| self.include_hidden || !is_hidden(e));
+            for entry in walker {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(error) => {
+                        inputs.push(unreadable(error)?);
+                        continue;
+                    }
+                };
+                let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+                if matcher.is_match(relative) {
+                    inputs.push(match base.is_empty() {
+                        true => (InputType::new_file(relative), None),
+                        false => (InputType::new_file(entry.path()), None),
+                    });
+                }
+            }
+        }
+
+        match inputs.is_empty() {
+            true => Err(NoGlobMatches(self.value().into())),
+            false => Ok(inputs),
+        }
+    }
+}
+
+impl DirectoryInput {
+    /// Create a new [`DirectoryInput`]
+    pub fn new<P: AsRef<Path>>(value: P) -> Self {
+        Self {
+            value: value.as_ref().into(),
+            recursive: false,
+            max_depth: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            include_directories: false,
+            include_hidden: false,
+        }
+    }
+
+    /// Descend into subdirectories
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Limit how many levels of subdirectories are descended into when recursive, 1 is the directory itself
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Appends a glob pattern that entries must match, relative to the directory, to be included
+    pub fn include<S: AsRef<str>>(mut self, pattern: S) -> Self {
+        self.include.push(pattern.as_ref().into());
+        self
+    }
+
+    /// Appends a glob pattern that excludes matching entries, relative to the directory, and their contents
+    pub fn exclude<S: AsRef<str>>(mut self, pattern: S) -> Self {
+        self.exclude.push(pattern.as_ref().into());
+        self
+    }
+
+    /// Include the subdirectories themselves as inputs
+    pub fn include_directories(mut self, include_directories: bool) -> Self {
+        self.include_directories = include_directories;
+        self
+    }
+
+    /// Include entries starting with `.`
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /// Return value as a [`Path`]
+    pub fn value(&self) -> &Path {
+        &self.value
+    }
+
+    /// Walk the directory and return a [`FileInput`] for each entry matching the configuration
+    ///
+    /// Paths that could not be read are logged and left out.
+    pub fn expand(&self) -> Result<Vec<InputType>, Error> {
+        Ok(readable(self.expand_entries()?))
+    }
+
+    fn expand_entries(&self) -> Result<Vec<(InputType, Option<String>)>, Error> {
+        let include = glob_set(self.include.as_slice())?;
+        let exclude = glob_set(self.exclude.as_slice())?;
+        let max_depth = match self.recursive {
+            true => self.max_depth.unwrap_or(usize::MAX),
+            false => 1,
+        };
+
+        let walker = WalkDir::new(self.value())
+            .min_depth(1)
+            .max_depth(max_depth)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| {
+                (self.include_hidden || !is_hidden(e))
+                    && !exclude.is_match(self.relative_path(e.path()))
+            });
+
+        let mut inputs = Vec::new();
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => {
+                    inputs.push(unreadable(error)?);
+                    continue;
+                }
+            };
+            if entry.file_type().is_dir() && !self.include_directories {
+                continue;
+            }
+            if !include.is_empty() && !include.is_match(self.relative_path(entry.path())) {
+                continue;
+            }
+            inputs.push((InputType::new_file(entry.path()), None));
+        }
+        Ok(inputs)
+    }
+
+    fn relative_path<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(self.value()).unwrap_or(path)
+    }
+}
+
+fn glob_set<S: AsRef<str>>(patterns: &[S]) -> Result<GlobSet, Error> {
+    let globs = patterns
+        .iter()
+        .map(|p| Glob::new(p.as_ref()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(GlobSet::new(globs)?)
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> Result<PathBuf, Error> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(std::ffi::OsString::from_vec(bytes).into())
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> Result<PathBuf, Error> {
+    Ok(String::from_utf8(bytes)
+        .map_err(|e| InvalidValue(e.to_string()))?
+        .into())
+}
+
+fn expand_home(pattern: &str) -> String {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (pattern.strip_prefix(HOME_PREFIX), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(GLOB_SEPARATOR) => {
+            format!("{}{}", home.to_string_lossy(), rest)
+        }
+        _ => pattern.into(),
+    }
+}
+
+// Report a path that could not be read with the reason instead of stopping the walk
+fn unreadable(error: walkdir::Error) -> Result<(InputType, Option<String>), Error> {
+    let reason = match error.io_error() {
+        Some(io_error) => format!("path could not be read: {io_error}"),
+        None => format!("path could not be read: {error}"),
+    };
+    match error.path() {
+        Some(path) => Ok((InputType::new_file(path), Some(reason))),
+        None => Err(error.into()),
+    }
+}
+
+fn readable(entries: Vec<(InputType, Option<String>)>) -> Vec<InputType> {
+    entries
+        .into_iter()
+        .filter_map(|(input, reason)| match reason {
+            Some(reason) => {
+                warn!("Leaving out {input:?}: {reason}");
+                None
+            }
+            None => Some(input),
+        })
+        .collect()
+}
+
+fn is_hidden(entry: &DirEntry) -> bool {
+    entry
+        .file_name()
+        .as_encoded_bytes()
+        .starts_with(HIDDEN_PREFIX.as_bytes())
+}
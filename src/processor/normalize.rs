@@ -0,0 +1,85 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+const ZERO_WIDTH_CHARS: [char; 5] = ['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
+
+/// Used with [`Normalizer`] to indicate which Unicode normalization form should be produced
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum NormalizationForm {
+    /// Canonical composition
+    Nfc,
+    /// Canonical decomposition
+    Nfd,
+}
+
+/// Represents Unicode normalization and transliteration applied to a value
+#[derive(Debug, Clone)]
+pub struct Normalizer {
+    form: NormalizationForm,
+    strip_diacritics: bool,
+    ascii: bool,
+    remove_zero_width: bool,
+}
+
+impl Normalizer {
+    /// Create a new [`Normalizer`] that produces the provided [`NormalizationForm`]
+    pub fn new(form: NormalizationForm) -> Self {
+        Self {
+            form,
+            strip_diacritics: false,
+            ascii: false,
+            remove_zero_width: false,
+        }
+    }
+
+    /// Remove combining marks such as accents from the value
+    pub fn strip_diacritics(mut self, strip_diacritics: bool) -> Self {
+        self.strip_diacritics = strip_diacritics;
+        self
+    }
+
+    /// Transliterate the value to ASCII, characters without a transliteration are removed
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Remove zero-width spaces, joiners and byte order marks from the value
+    pub fn remove_zero_width(mut self, remove_zero_width: bool) -> Self {
+        self.remove_zero_width = remove_zero_width;
+        self
+    }
+
+    /// Process the provided value based on the [`Normalizer`] configuration
+    pub fn normalize<S: AsRef<str>>(&self, value: S) -> String {
+        let mut output: String = match self.remove_zero_width {
+            true => value
+                .as_ref()
+                .chars()
+                .filter(|c| !ZERO_WIDTH_CHARS.contains(c))
+                .collect(),
+            false => value.as_ref().into(),
+        };
+        if self.strip_diacritics || self.ascii {
+            output = output.nfd().filter(|c| !is_combining_mark(*c)).collect();
+        }
+        if self.ascii {
+            output = output
+                .chars()
+                .map(|c| match c.is_ascii() {
+                    true => c.to_string(),
+                    false => deunicode::deunicode_char(c).unwrap_or_default().into(),
+                })
+                .collect();
+        }
+        match self.form {
+            NormalizationForm::Nfc => output.nfc().collect(),
+            NormalizationForm::Nfd => output.nfd().collect(),
+        }
+    }
+
+    /// Process the provided value based on the [`Normalizer`] configuration for each value in the slice
+    pub fn normalize_slice<S: AsRef<str>>(&self, values: &[S]) -> Vec<String> {
+        values.iter().map(|v| self.normalize(v)).collect()
+    }
+}
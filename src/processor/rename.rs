@@ -1,8 +1,15 @@
 use crate::Error;
 use crate::Format;
+use crate::Outcome;
+use crate::batch::{Collision, CollisionKind};
+use crate::path::{absolute_path, is_same_file};
+use crate::processor::encoding::{escape_os_str, unescape_os_str};
 use crate::processor::format::FormatType;
+use crate::processor::symlink::Link;
+use crate::transfer::{FileAction, Verification, transfer};
+use std::ffi::OsStr;
 use std::fmt::Debug;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 const EMPTY_STR: &str = "";
 
@@ -22,33 +29,66 @@ pub trait Renamed: Debug {
 
     /// Take an action to commit the renaming process to the underlying item(i.e. actually rename the file no the filesystem)
     fn action(&self) -> Result<(), Error>;
+
+    /// Return the reason the item was skipped during processing, if it was
+    fn skip_reason(&self) -> Option<&str> {
+        None
+    }
+
+    /// Return the underlying [`RenamedFile`] when the item represents a file system path
+    fn as_file(&self) -> Option<&RenamedFile> {
+        None
+    }
+
+    /// Return `true` when the new value is the same as the original
+    fn is_unchanged(&self) -> bool {
+        self.original() == self.future()
+    }
+
+    /// Take the action unless the item was skipped or is unchanged and return the [`Outcome`]
+    fn apply(&self) -> Outcome {
+        if let Some(reason) = self.skip_reason() {
+            return Outcome::Skipped(reason.to_string());
+        }
+        if self.is_unchanged() {
+            return Outcome::Unchanged;
+        }
+        match self.action() {
+            Ok(_) => Outcome::Renamed,
+            Err(e @ Error::LinkNotUpdated { .. }) => Outcome::LinkNotUpdated(e),
+            Err(e) => Outcome::Failed(e),
+        }
+    }
 }
 
 /// Represents a file for the purpose on implementing the [`RenameProcessor`] trait
 #[derive(Debug)]
 pub(super) struct FileRenamer {
-    segments: Vec<String>,
-    selected: Vec<Option<String>>,
-    extracted: Vec<Option<String>>,
-    format: Format,
+    new_name: String,
     original_path: PathBuf,
+    escaped: bool,
+    destination: Option<PathBuf>,
+    file_action: FileAction,
+    link: Option<Link>,
 }
 
-/// Represents a file for the purpose on implementing the [`Renamed`] trait
+/// Represents a file or directory for the purpose on implementing the [`Renamed`] trait
+///
+/// Taking the action fails with [`Error::Collisions`] when another file is at the new path, a
+/// [`BatchBuilder`][crate::BatchBuilder] with a [`ConflictPolicy`][crate::ConflictPolicy] can replace it.
 #[derive(Debug)]
 pub struct RenamedFile {
     original_path: PathBuf,
     new_path: PathBuf,
     original_name: String,
     new_name: String,
+    file_action: FileAction,
+    link: Option<Link>,
 }
 
 #[derive(Debug)]
 pub(super) struct TextRenamer {
-    segments: Vec<String>,
-    selected: Vec<Option<String>>,
-    extracted: Vec<Option<String>>,
-    format: Format,
+    new_name: String,
     original_string: String,
 }
 
@@ -58,34 +98,64 @@ pub struct RenamedText {
     new: String,
 }
 
+/// Represents an input that was not processed for the purpose on implementing the [`Renamed`] trait
+#[derive(Debug)]
+pub struct SkippedInput {
+    original: String,
+    reason: String,
+}
+
 impl RenameProcessor for FileRenamer {
     fn rename(&self) -> Box<dyn Renamed> {
-        let new_name = process_format(
-            self.segments.as_slice(),
-            self.selected.as_slice(),
-            self.extracted.as_slice(),
-            &self.format,
-        );
-        let renamed = RenamedFile::new(self.original_path.as_path(), new_name);
-        Box::new(renamed)
+        let renamed = match self.escaped {
+            true => RenamedFile::new_escaped(self.original_path.as_path(), self.new_name.as_str()),
+            false => RenamedFile::new(self.original_path.as_path(), self.new_name.as_str()),
+        };
+        Box::new(
+            renamed
+                .with_destination(self.destination.as_deref())
+                .with_link(self.link.clone())
+                .with_file_action(self.file_action),
+        )
     }
 }
 
 impl FileRenamer {
     /// Create a new [`FileRenamer`]
-    pub fn new<P: AsRef<Path>>(
-        original_path: P,
-        segments: Vec<String>,
-        selected: Vec<Option<String>>,
-        extracted: Vec<Option<String>>,
-        format: Format,
-    ) -> Self {
+    pub fn new<P: AsRef<Path>, S: AsRef<str>>(original_path: P, new_name: S) -> Self {
         Self {
-            segments,
-            selected,
-            extracted,
-            format,
+            new_name: new_name.as_ref().into(),
             original_path: original_path.as_ref().into(),
+            escaped: false,
+            destination: None,
+            file_action: FileAction::default(),
+            link: None,
+        }
+    }
+
+    /// Sets the directory the new name is relative to instead of the directory of the original path
+    pub fn destination(mut self, destination: Option<PathBuf>) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    /// Sets the [`FileAction`] taken at the new path
+    pub fn file_action(mut self, file_action: FileAction) -> Self {
+        self.file_action = file_action;
+        self
+    }
+
+    /// Sets the symbolic link that points at the original path and is updated after renaming
+    pub fn link(mut self, link: Option<Link>) -> Self {
+        self.link = link;
+        self
+    }
+
+    /// Create a new [`FileRenamer`] where the new name contains values escaped with [`escape_os_str`]
+    pub fn new_escaped<P: AsRef<Path>, S: AsRef<str>>(original_path: P, new_name: S) -> Self {
+        Self {
+            escaped: true,
+            ..Self::new(original_path, new_name)
         }
     }
 }
@@ -100,21 +170,106 @@ impl Renamed for RenamedFile {
     }
 
     fn action(&self) -> Result<(), Error> {
-        Ok(std::fs::rename(self.original_path(), self.new_path())?)
+        if self.is_unchanged() {
+            return Ok(());
+        }
+        // Only a batch with a ConflictPolicy may replace an existing file
+        if self.new_path().symlink_metadata().is_ok()
+            && !is_same_file(self.original_path(), self.new_path())
+        {
+            return Err(Error::Collisions(vec![Collision::new(
+                CollisionKind::Exists,
+                self.new_path(),
+                vec![self.original_path().into()],
+            )]));
+        }
+        transfer(
+            self.original_path(),
+            self.new_path(),
+            self.file_action,
+            Verification::default(),
+        )?;
+        match self.file_action.keeps_source() {
+            true => Ok(()),
+            false => self.update_link(),
+        }
+    }
+
+    fn as_file(&self) -> Option<&RenamedFile> {
+        Some(self)
+    }
+
+    fn is_unchanged(&self) -> bool {
+        absolute_path(self.original_path()) == absolute_path(self.new_path())
     }
 }
 
 impl RenamedFile {
     /// Create a new [`RenamedFile`]
     pub fn new<P: AsRef<Path>, S: AsRef<str>>(original_path: P, new_name: S) -> Self {
-        let mut new_path = original_path.as_ref().to_path_buf();
-        new_path.set_file_name(new_name.as_ref());
+        Self::with_file_name(
+            original_path.as_ref(),
+            filename_as_string_lossy(original_path.as_ref()),
+            new_name.as_ref(),
+            new_name.as_ref().as_ref(),
+        )
+    }
+
+    /// Create a new [`RenamedFile`] where the names are escaped and the file name is restored from the new name
+    pub(super) fn new_escaped<P: AsRef<Path>, S: AsRef<str>>(
+        original_path: P,
+        new_name: S,
+    ) -> Self {
+        Self::with_file_name(
+            original_path.as_ref(),
+            escape_os_str(original_path.as_ref().file_name().unwrap_or_default()),
+            new_name.as_ref(),
+            unescape_os_str(new_name.as_ref()).as_os_str(),
+        )
+    }
+
+    fn with_file_name(
+        original_path: &Path,
+        original_name: String,
+        new_name: &str,
+        file_name: &OsStr,
+    ) -> Self {
+        // A new name containing `/` moves the item into a path relative to its original directory
+        let new_path = match original_path.parent() {
+            Some(parent) => parent.join(file_name),
+            None => PathBuf::from(file_name),
+        };
         Self {
-            original_path: original_path.as_ref().to_path_buf(),
-            original_name: filename_as_string_lossy(original_path.as_ref()),
+            original_path: original_path.to_path_buf(),
+            original_name,
             new_path,
-            new_name: new_name.as_ref().to_string(),
+            new_name: new_name.to_string(),
+            file_action: FileAction::default(),
+            link: None,
+        }
+    }
+
+    pub(super) fn with_destination(mut self, destination: Option<&Path>) -> Self {
+        if let Some(destination) = destination {
+            let relative = self
+                .original_path
+                .parent()
+                .and_then(|parent| self.new_path.strip_prefix(parent).ok())
+                .unwrap_or(self.new_path.as_path());
+            self.new_path = destination.join(relative);
         }
+        self
+    }
+
+    pub(super) fn with_link(mut self, link: Option<Link>) -> Self {
+        self.link = link;
+        self
+    }
+
+    /// Sets the [`FileAction`] taken at the new path
+    pub fn with_file_action(mut self, file_action: FileAction) -> Self {
+        self.file_action = file_action;
+        self
     }
 
     /// Return the original file path
@@ -136,22 +291,40 @@ impl RenamedFile {
     pub fn new_name(&self) -> &str {
         &self.new_name
     }
+
+    /// Return the [`FileAction`] taken at the new path
+    pub fn file_action(&self) -> FileAction {
+        self.file_action
+    }
+
+    /// Return the path of the symbolic link that is pointed at the new path after renaming
+    ///
+    /// Links are only updated when the file is moved.
+    pub fn link_path(&self) -> Option<&Path> {
+        match self.file_action.keeps_source() {
+            true => None,
+            false => self.link.as_ref().map(|l| l.path()),
+        }
+    }
+
+    /// Point the symbolic link, if there is one, at the new path
+    pub(crate) fn update_link(&self) -> Result<(), Error> {
+        if let Some(link) = &self.link {
+            link.update(self.new_path())
+                .map_err(|source| Error::LinkNotUpdated {
+                    link: link.path().into(),
+                    source,
+                })?;
+        }
+        Ok(())
+    }
 }
 
 impl TextRenamer {
     /// Create a new [`TextRenamer`]
-    pub fn new<S: AsRef<str>>(
-        original_string: S,
-        segments: Vec<String>,
-        selected: Vec<Option<String>>,
-        extracted: Vec<Option<String>>,
-        format: Format,
-    ) -> Self {
+    pub fn new<S: AsRef<str>>(original_string: S, new_name: S) -> Self {
         Self {
-            segments,
-            selected,
-            extracted,
-            format,
+            new_name: new_name.as_ref().into(),
             original_string: original_string.as_ref().into(),
         }
     }
@@ -159,13 +332,7 @@ impl TextRenamer {
 
 impl RenameProcessor for TextRenamer {
     fn rename(&self) -> Box<dyn Renamed> {
-        let new_name = process_format(
-            self.segments.as_slice(),
-            self.selected.as_slice(),
-            self.extracted.as_slice(),
-            &self.format,
-        );
-        let renamed = RenamedText::new(self.original_string.as_str(), new_name.as_str());
+        let renamed = RenamedText::new(self.original_string.as_str(), self.new_name.as_str());
         Box::new(renamed)
     }
 }
@@ -194,6 +361,54 @@ impl Renamed for RenamedText {
     }
 }
 
+impl SkippedInput {
+    /// Create a new [`SkippedInput`]
+    pub fn new<S: AsRef<str>, R: AsRef<str>>(original: S, reason: R) -> Self {
+        Self {
+            original: original.as_ref().into(),
+            reason: reason.as_ref().into(),
+        }
+    }
+}
+
+impl Renamed for SkippedInput {
+    fn original(&self) -> &str {
+        self.original.as_str()
+    }
+
+    fn future(&self) -> &str {
+        self.original.as_str()
+    }
+
+    fn action(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn skip_reason(&self) -> Option<&str> {
+        Some(self.reason.as_str())
+    }
+}
+
+/// Return the reason a new file name cannot be used, it must be relative and only made of names separated by `/`
+///
+/// Other directory trees are reached with [`ProcessorBuilder::destination`][crate::ProcessorBuilder::destination].
+pub(crate) fn invalid_name_reason(new_name: &str) -> Option<String> {
+    let mut components = Path::new(new_name).components().peekable();
+    if components.peek().is_none() {
+        return Some("new name is empty".to_string());
+    }
+    components.find_map(|component| match component {
+        Component::Normal(_) => None,
+        Component::Prefix(_) | Component::RootDir => {
+            Some(format!("new name {new_name} is an absolute path"))
+        }
+        Component::CurDir | Component::ParentDir => Some(format!(
+            "new name {new_name} contains {}",
+            component.as_os_str().to_string_lossy()
+        )),
+    })
+}
+
 /// Get a filename from a provided path as a [`String`]
 pub(crate) fn filename_as_string_lossy<P: AsRef<Path>>(path: P) -> String {
     path.as_ref()
@@ -204,7 +419,7 @@ pub(crate) fn filename_as_string_lossy<P: AsRef<Path>>(path: P) -> String {
 }
 
 // Create future from original
-fn process_format(
+pub(super) fn process_format(
     segments: &[String],
     selected: &[Option<String>],
     extracted: &[Option<String>],
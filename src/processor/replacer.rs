@@ -1,30 +1,156 @@
-use regex::Regex;
+use crate::Selector;
+use crate::error::Error;
+use crate::processor::dictionary::{Dictionary, DictionaryMatch};
+use regex::{NoExpand, Regex, RegexBuilder};
+use std::path::Path;
 
-/// Represents a [`Regex`]  that is used to replace matches with a provided substitution
+/// Represents how a [`Replacer`] finds and substitutes values
+#[derive(Debug, Clone)]
+enum ReplacerKind {
+    Regex {
+        pattern: Regex,
+        substitute: String,
+        literal: bool,
+    },
+    Dictionary(Dictionary),
+}
+
+/// Represents which segments a [`Replacer`] is applied to
+#[derive(Debug, Clone)]
+enum ReplacerTarget {
+    All,
+    Segments(Vec<usize>),
+    Selector(Selector),
+}
+
+/// Represents a [`Regex`] or dictionary that is used to replace matches with a provided substitution
 #[derive(Debug, Clone)]
 pub struct Replacer {
-    pattern: Regex,
-    substitute: String,
+    kind: ReplacerKind,
+    limit: usize,
+    target: ReplacerTarget,
 }
 
 impl Replacer {
     /// Create a new [`Replacer`]
     pub fn new<S: AsRef<str>>(pattern: Regex, substitute: S) -> Self {
-        Self {
+        Self::with_kind(ReplacerKind::Regex {
             pattern,
             substitute: substitute.as_ref().into(),
+            literal: false,
+        })
+    }
+
+    /// Create a new [`Replacer`] that matches the plain text value and inserts the substitute as is
+    pub fn literal<S: AsRef<str>>(
+        value: S,
+        substitute: S,
+        case_insensitive: bool,
+    ) -> Result<Self, Error> {
+        let pattern = RegexBuilder::new(regex::escape(value.as_ref()).as_str())
+            .case_insensitive(case_insensitive)
+            .build()?;
+        Ok(Self::with_kind(ReplacerKind::Regex {
+            pattern,
+            substitute: substitute.as_ref().into(),
+            literal: true,
+        }))
+    }
+
+    /// Create a new [`Replacer`] from pairs of original and substitute values
+    pub fn dictionary<S: AsRef<str>>(
+        mappings: Vec<(S, S)>,
+        match_type: DictionaryMatch,
+    ) -> Result<Self, Error> {
+        Ok(Self::with_kind(ReplacerKind::Dictionary(Dictionary::new(
+            mappings, match_type,
+        )?)))
+    }
+
+    /// Create a new [`Replacer`] from a mapping file with one original and substitute pair per line
+    ///
+    /// Files with a `csv` extension are read as comma separated values, all others as tab separated values.
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn dictionary_from_file<P: AsRef<Path>>(
+        path: P,
+        match_type: DictionaryMatch,
+    ) -> Result<Self, Error> {
+        Ok(Self::with_kind(ReplacerKind::Dictionary(
+            Dictionary::from_file(path, match_type)?,
+        )))
+    }
+
+    fn with_kind(kind: ReplacerKind) -> Self {
+        Self {
+            kind,
+            limit: 0,
+            target: ReplacerTarget::All,
         }
     }
 
+    /// Limit the number of replacements made in each value, 0 replaces all matches
+    ///
+    /// A [`DictionaryMatch::WholeSegment`] dictionary makes at most one replacement in each value so every limit allows it.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Only apply to the segments at the provided positions, numbered from 1 as in the `%dN%` format pattern
+    pub fn segments(mut self, positions: Vec<usize>) -> Self {
+        self.target = ReplacerTarget::Segments(positions);
+        self
+    }
+
+    /// Only apply to the segments matched by the provided [`Selector`]
+    pub fn selector(mut self, selector: Selector) -> Self {
+        self.target = ReplacerTarget::Selector(selector);
+        self
+    }
+
     /// Replaces all matches with the provided substitute value
     pub fn replace<S: AsRef<str>>(&self, value: S) -> String {
-        self.pattern
-            .replace_all(value.as_ref(), self.substitute.as_str())
-            .into()
+        match &self.kind {
+            ReplacerKind::Regex {
+                pattern,
+                substitute,
+                literal: true,
+            } => pattern
+                .replacen(value.as_ref(), self.limit, NoExpand(substitute.as_str()))
+                .into(),
+            ReplacerKind::Regex {
+                pattern,
+                substitute,
+                literal: false,
+            } => pattern
+                .replacen(value.as_ref(), self.limit, substitute.as_str())
+                .into(),
+            ReplacerKind::Dictionary(dictionary) => dictionary.replace(value.as_ref(), self.limit),
+        }
     }
 
-    /// Replaces all matches with the provided substitute value for each value in the slice
+    /// Replaces all matches with the provided substitute value for each targeted value in the slice
     pub fn replace_slice<S: AsRef<str>>(&self, values: &[S]) -> Vec<String> {
-        values.iter().map(|v| self.replace(v)).collect()
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| match self.is_target(i, v.as_ref()) {
+                true => self.replace(v),
+                false => v.as_ref().into(),
+            })
+            .collect()
+    }
+
+    /// Return `true` when only some segments are replaced
+    pub(crate) fn is_targeted(&self) -> bool {
+        !matches!(self.target, ReplacerTarget::All)
+    }
+
+    fn is_target(&self, index: usize, value: &str) -> bool {
+        match &self.target {
+            ReplacerTarget::All => true,
+            ReplacerTarget::Segments(positions) => positions.contains(&(index + 1)),
+            ReplacerTarget::Selector(selector) => selector.is_match(value),
+        }
     }
 }
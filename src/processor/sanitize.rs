@@ -0,0 +1,177 @@
+use crate::processor::encoding::escaped_unit_len;
+
+const DEFAULT_REPLACEMENT: &str = "_";
+const DIRECTORY_SEPARATOR: char = '/';
+const EXTENSION_SEPARATOR: char = '.';
+const ESCAPED_BACKSLASH: &str = "\\\\";
+const INVALID_CHARS: [char; 8] = ['<', '>', ':', '"', '|', '?', '*', '\\'];
+const NAME_MAX: usize = 255;
+const RESERVED_NAMES: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+const RESERVED_NUMBERED_NAMES: [&str; 2] = ["COM", "LPT"];
+
+/// Represents the clean up of a rendered name so it is valid on Linux, macOS and Windows file systems
+///
+/// `/` separates directories as in the rendered [`Format`][crate::Format] and each part between them is sanitized on its own,
+/// empty parts as well as `.` and `..` are removed. `\` is replaced as well, except in the escapes of file names that
+/// are not valid UTF-8.
+#[derive(Debug, Clone)]
+pub struct Sanitizer {
+    replacement: String,
+    directories: bool,
+    max_length: usize,
+}
+
+impl Default for Sanitizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sanitizer {
+    /// Create a new [`Sanitizer`] replacing invalid characters with `_` and limiting names to 255 bytes
+    pub fn new() -> Self {
+        Self {
+            replacement: DEFAULT_REPLACEMENT.to_string(),
+            directories: true,
+            max_length: NAME_MAX,
+        }
+    }
+
+    /// Sets the value invalid characters are replaced with, an empty value removes them
+    pub fn replacement<S: AsRef<str>>(mut self, replacement: S) -> Self {
+        self.replacement = replacement.as_ref().to_string();
+        self
+    }
+
+    /// Sets whether `/` separates directories, when `false` it is replaced like other invalid characters
+    pub fn directories(mut self, directories: bool) -> Self {
+        self.directories = directories;
+        self
+    }
+
+    /// Sets the largest length in bytes of each name, names are shortened on character boundaries keeping the extension when part of the stem fits
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Return the sanitized value
+    pub fn sanitize<S: AsRef<str>>(&self, value: S) -> String {
+        self.sanitize_value(value.as_ref(), false)
+    }
+
+    /// Return the sanitized value keeping the escapes of a file name that is not valid UTF-8
+    pub(crate) fn sanitize_escaped<S: AsRef<str>>(&self, value: S) -> String {
+        self.sanitize_value(value.as_ref(), true)
+    }
+
+    fn sanitize_value(&self, value: &str, escaped: bool) -> String {
+        match self.directories {
+            true => {
+                let names: Vec<_> = value
+                    .split(DIRECTORY_SEPARATOR)
+                    .filter(|name| !matches!(*name, "" | "." | ".."))
+                    .map(|name| self.sanitize_name(name, escaped))
+                    .collect();
+                match names.is_empty() {
+                    true => self.sanitize_name("", escaped),
+                    false => names.join(DIRECTORY_SEPARATOR.to_string().as_str()),
+                }
+            }
+            false => self.sanitize_name(value, escaped),
+        }
+    }
+
+    fn sanitize_name(&self, name: &str, escaped: bool) -> String {
+        let mut sanitized = String::with_capacity(name.len());
+        let mut rest = name;
+        while let Some(c) = rest.chars().next() {
+            let len = match escaped.then(|| escaped_unit_len(rest)).flatten() {
+                Some(len) => {
+                    sanitized.push_str(&rest[..len]);
+                    len
+                }
+                None if escaped && rest.starts_with(ESCAPED_BACKSLASH) => {
+                    sanitized.push_str(self.replacement.as_str());
+                    ESCAPED_BACKSLASH.len()
+                }
+                None => {
+                    match is_invalid(c) {
+                        true => sanitized.push_str(self.replacement.as_str()),
+                        false => sanitized.push(c),
+                    }
+                    c.len_utf8()
+                }
+            };
+            rest = &rest[len..];
+        }
+        let sanitized = truncate(trim_end(sanitized.as_str()), self.max_length);
+        match sanitized.is_empty() || is_reserved(sanitized.as_str()) {
+            true => self.mark_reserved(sanitized.as_str()),
+            false => sanitized,
+        }
+    }
+
+    // Add the replacement, or `_` when it is empty, after the stem so the name is no longer reserved,
+    // shortening the name first so the marker fits
+    fn mark_reserved(&self, name: &str) -> String {
+        let marker = match self.replacement.is_empty() {
+            true => DEFAULT_REPLACEMENT,
+            false => self.replacement.as_str(),
+        };
+        let name = truncate(name, self.max_length.saturating_sub(marker.len()));
+        let (stem, extension) = name.split_at(name.find(EXTENSION_SEPARATOR).unwrap_or(name.len()));
+        format!("{stem}{marker}{extension}")
+    }
+}
+
+fn is_invalid(c: char) -> bool {
+    c.is_ascii_control() || c == DIRECTORY_SEPARATOR || INVALID_CHARS.contains(&c)
+}
+
+// Windows does not allow names ending with dots or spaces
+fn trim_end(name: &str) -> &str {
+    name.trim_end_matches(['.', ' '])
+}
+
+// Windows reserves device names whatever the extension and case
+fn is_reserved(name: &str) -> bool {
+    let stem = name
+        .split(EXTENSION_SEPARATOR)
+        .next()
+        .unwrap_or_default()
+        .trim_end();
+    RESERVED_NAMES.iter().any(|r| stem.eq_ignore_ascii_case(r))
+        || RESERVED_NUMBERED_NAMES.iter().any(|r| {
+            stem.len() == r.len() + 1
+                && stem.is_char_boundary(r.len())
+                && stem[..r.len()].eq_ignore_ascii_case(r)
+                && stem[r.len()..].chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+// Keep the extension only when part of the stem fits in front of it
+fn truncate(name: &str, max_length: usize) -> String {
+    if name.len() <= max_length {
+        return name.to_string();
+    }
+    if let Some(i) = name.rfind(EXTENSION_SEPARATOR).filter(|i| *i > 0) {
+        let (stem, extension) = name.split_at(i);
+        let stem = trim_end(floor_char_boundary(
+            stem,
+            max_length.saturating_sub(extension.len()),
+        ));
+        if !stem.is_empty() {
+            return format!("{stem}{extension}");
+        }
+    }
+    trim_end(floor_char_boundary(name, max_length)).to_string()
+}
+
+fn floor_char_boundary(value: &str, index: usize) -> &str {
+    let end = (0..=index.min(value.len()))
+        .rev()
+        .find(|i| value.is_char_boundary(*i))
+        .unwrap_or_default();
+    &value[..end]
+}
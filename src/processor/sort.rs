@@ -0,0 +1,158 @@
+use crate::Extractor;
+use crate::processor::inputs::InputType;
+use crate::processor::rename::filename_as_string_lossy;
+use crate::processor::symlink::metadata;
+use std::cmp::Ordering;
+use std::time::SystemTime;
+
+/// Used with the [`ProcessorBuilder`][crate::ProcessorBuilder] to choose the order inputs are processed in
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum SortMode {
+    /// Keep the order the inputs were added in
+    #[default]
+    Insertion,
+    /// Order by name ignoring case with runs of digits compared by their numeric value, so `2` is before `10`
+    Natural,
+    /// Order by name comparing characters
+    Lexical,
+    /// Order by file modification time, oldest first
+    ///
+    /// Inputs without a modification time are ordered last, also when the order is reversed, and files whose metadata
+    /// cannot be read are reported as skipped.
+    Modified,
+    /// Order by file size, smallest first
+    ///
+    /// Inputs without a size are ordered last, also when the order is reversed, and files whose metadata cannot be read
+    /// are reported as skipped.
+    Size,
+    /// Order naturally by the value of the [`Extractor`] at the position provided, numbered from 1 as in the `%eN%` format pattern
+    ///
+    /// Inputs without an extracted value are ordered last, also when the order is reversed.
+    Extracted(usize),
+}
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum SortKey {
+    None,
+    Name(String),
+    Size(u64),
+    Time(SystemTime),
+}
+
+impl SortMode {
+    /// Sort the inputs, paired with the reason they are skipped, based on the [`SortMode`], `reverse` inverts the resulting order except for inputs without a value to sort by
+    pub(crate) fn sort(
+        &self,
+        inputs: Vec<(InputType, Option<String>)>,
+        extractors: &[Extractor],
+        reverse: bool,
+        follow_symlinks: bool,
+    ) -> Vec<(InputType, Option<String>)> {
+        let mut keyed = Vec::with_capacity(inputs.len());
+        for (input, skip_reason) in inputs {
+            let (key, skip_reason) = match self.key(&input, extractors, follow_symlinks) {
+                Ok(key) => (key, skip_reason),
+                Err(error) => (
+                    SortKey::None,
+                    skip_reason.or_else(|| Some(format!("metadata could not be read: {error}"))),
+                ),
+            };
+            keyed.push((key, (input, skip_reason)));
+        }
+        keyed.sort_by(|(a, _), (b, _)| match (self, a, b) {
+            (SortMode::Natural | SortMode::Extracted(_), SortKey::Name(a), SortKey::Name(b)) => {
+                natural_cmp(a, b)
+            }
+            (_, SortKey::None, SortKey::None) => Ordering::Equal,
+            (_, SortKey::None, _) => Ordering::Greater,
+            (_, _, SortKey::None) => Ordering::Less,
+            _ => a.cmp(b),
+        });
+        if reverse {
+            // Inputs without a value to sort by stay last
+            let keyed_count = match self {
+                SortMode::Insertion => keyed.len(),
+                _ => keyed.partition_point(|(k, _)| *k != SortKey::None),
+            };
+            keyed[..keyed_count].reverse();
+        }
+        keyed.into_iter().map(|(_, input)| input).collect()
+    }
+
+    fn key(
+        &self,
+        input: &InputType,
+        extractors: &[Extractor],
+        follow_symlinks: bool,
+    ) -> std::io::Result<SortKey> {
+        let name = match input {
+            InputType::File(i) => filename_as_string_lossy(i.value()),
+            InputType::Text(i) => i.value().to_string(),
+            InputType::Directory(_) | InputType::Glob(_) => return Ok(SortKey::None),
+        };
+        Ok(match (self, input) {
+            (SortMode::Insertion, _) => SortKey::None,
+            (SortMode::Natural | SortMode::Lexical, _) => SortKey::Name(name),
+            (SortMode::Modified, InputType::File(i)) => {
+                SortKey::Time(metadata(i.value(), follow_symlinks)?.modified()?)
+            }
+            (SortMode::Size, InputType::File(i)) => {
+                SortKey::Size(metadata(i.value(), follow_symlinks)?.len())
+            }
+            (SortMode::Modified | SortMode::Size, _) => SortKey::None,
+            (SortMode::Extracted(position), _) => position
+                .checked_sub(1)
+                .and_then(|i| extractors.get(i))
+                .and_then(|e| e.extract(name))
+                .map_or(SortKey::None, SortKey::Name),
+        })
+    }
+}
+
+/// Compare two values ignoring case with runs of ASCII digits compared by their numeric value
+///
+/// Values that only differ by case or leading zeros are ordered by comparing their characters.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chunks = chunks(a);
+    let mut b_chunks = chunks(b);
+    loop {
+        let ordering = match (a_chunks.next(), b_chunks.next()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (is_digits(a), is_digits(b)) {
+                (true, true) => {
+                    let a = a.trim_start_matches('0');
+                    let b = b.trim_start_matches('0');
+                    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+                }
+                _ => folded(a).cmp(folded(b)),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+// Split a value into alternating runs of ASCII digits and other characters
+fn chunks(value: &str) -> impl Iterator<Item = &str> {
+    let mut rest = value;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != first.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (chunk, remainder) = rest.split_at(end);
+        rest = remainder;
+        Some(chunk)
+    })
+}
+
+fn folded(value: &str) -> impl Iterator<Item = char> {
+    value.chars().flat_map(char::to_lowercase)
+}
+
+fn is_digits(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_ascii_digit())
+}
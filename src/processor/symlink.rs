@@ -0,0 +1,114 @@
+use crate::batch::temporary_path;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+
+/// Used with the [`ProcessorBuilder`][crate::ProcessorBuilder] to choose how file inputs that are symbolic links are handled
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum SymlinkPolicy {
+    /// Process and rename the link itself, the target is unchanged
+    #[default]
+    Link,
+    /// Process and rename the target of the link and point the link at the renamed target
+    Target,
+    /// Do not process the link and report it as skipped
+    Skip,
+}
+
+/// Represents a symbolic link that points at a [`RenamedFile`][crate::RenamedFile] and is updated when it is renamed
+#[derive(Debug, Clone)]
+pub(crate) struct Link {
+    path: PathBuf,
+    target: PathBuf,
+}
+
+impl Link {
+    /// Read the symbolic link at the provided path
+    pub(crate) fn read<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Ok(Self {
+            path: path.as_ref().into(),
+            target: std::fs::read_link(path.as_ref())?,
+        })
+    }
+
+    /// Return the path of the link
+    pub(crate) fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// Return the path of the target, relative targets are resolved from the directory of the link
+    pub(crate) fn resolved_target(&self) -> PathBuf {
+        match self.path.parent() {
+            Some(parent) if self.target.is_relative() => parent.join(self.target.as_path()),
+            _ => self.target.clone(),
+        }
+    }
+
+    /// Replace the link with one pointing at the new target path, keeping relative targets relative
+    ///
+    /// The new link is created under a temporary name next to the link and renamed over it, so the link is kept when
+    /// creating the new one fails.
+    pub(crate) fn update<P: AsRef<Path>>(&self, new_target: P) -> std::io::Result<()> {
+        let resolved = self.resolved_target();
+        let moved = resolved
+            .parent()
+            .and_then(|parent| new_target.as_ref().strip_prefix(parent).ok());
+        let target = match (self.target.is_relative(), moved) {
+            (true, Some(moved)) => self
+                .target
+                .parent()
+                .map_or_else(|| moved.to_path_buf(), |parent| parent.join(moved)),
+            _ => new_target.as_ref().into(),
+        };
+        let temporary =
+            temporary_path(self.path.as_path(), |_| false).map_err(std::io::Error::other)?;
+        create_symlink(target.as_path(), temporary.as_path(), new_target.as_ref())?;
+        std::fs::rename(temporary.as_path(), self.path.as_path()).inspect_err(|_| {
+            if let Err(error) = remove_symlink(temporary.as_path(), new_target.as_ref()) {
+                log::warn!("Unable to remove temporary link {temporary:?}: {error}");
+            }
+        })
+    }
+}
+
+/// Read metadata from the target of a symbolic link when `follow` is set, otherwise from the link itself
+pub(crate) fn metadata<P: AsRef<Path>>(path: P, follow: bool) -> std::io::Result<Metadata> {
+    match follow {
+        true => path.as_ref().metadata(),
+        false => path.as_ref().symlink_metadata(),
+    }
+}
+
+#[cfg(windows)]
+fn remove_symlink(link: &Path, resolved: &Path) -> std::io::Result<()> {
+    match resolved.is_dir() {
+        true => std::fs::remove_dir(link),
+        false => std::fs::remove_file(link),
+    }
+}
+
+#[cfg(not(windows))]
+fn remove_symlink(link: &Path, _resolved: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(link)
+}
+
+#[cfg(unix)]
+pub(crate) fn create_symlink(target: &Path, link: &Path, _resolved: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+pub(crate) fn create_symlink(target: &Path, link: &Path, resolved: &Path) -> std::io::Result<()> {
+    match resolved.is_dir() {
+        true => std::os::windows::fs::symlink_dir(target, link),
+        false => std::os::windows::fs::symlink_file(target, link),
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn create_symlink(
+    _target: &Path,
+    _link: &Path,
+    _resolved: &Path,
+) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
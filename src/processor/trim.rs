@@ -1,57 +1,164 @@
-#[derive(Debug, Copy, Clone)]
-enum TrimType {
+use crate::error::Error;
+use regex::Regex;
+
+/// Used with [`Trim`] to indicate which side of a value should be trimmed
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum TrimDirection {
+    /// Trim only the start of a value
     Left,
+    /// Trim only the end of a value
     Right,
+    /// Trim both the start and the end of a value
     Both,
 }
 
-/// Represents a [`String`] to be trimmed from a value based on the provided configuration
+/// Used with [`Trim`] to indicate how the provided pattern should be interpreted
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum TrimType {
+    /// Pattern is a plain [`String`] that is removed as a whole
+    String,
+    /// Pattern is a [`Regex`] anchored to the side being trimmed
+    Regex,
+    /// Pattern is a set of characters, any one of which is removed
+    Chars,
+    /// Unicode whitespace is removed, created with [`Trim::whitespace`] as no pattern is needed
+    Whitespace,
+}
+
+/// Represents a pattern to be trimmed from a value based on the provided configuration
 #[derive(Debug, Clone)]
 pub struct Trim {
-    direction: TrimType,
+    direction: TrimDirection,
+    trim_type: TrimType,
     pattern: String,
+    start_regex: Option<Regex>,
+    end_regex: Option<Regex>,
+    limit: Option<usize>,
 }
 
 impl Trim {
+    /// Create a new [`Trim`], the pattern is ignored for [`TrimType::Whitespace`]
+    pub fn new<S: AsRef<str>>(
+        pattern: S,
+        trim_type: TrimType,
+        direction: TrimDirection,
+    ) -> Result<Self, Error> {
+        let (start_regex, end_regex) = match trim_type {
+            TrimType::Regex => (
+                Some(Regex::new(format!("^(?:{})", pattern.as_ref()).as_str())?),
+                Some(Regex::new(format!("(?:{})$", pattern.as_ref()).as_str())?),
+            ),
+            _ => (None, None),
+        };
+        Ok(Self {
+            direction,
+            trim_type,
+            pattern: pattern.as_ref().into(),
+            start_regex,
+            end_regex,
+            limit: None,
+        })
+    }
+
     /// Create a [`Trim`] item that will remove values from both ends of a value
     pub fn both<S: AsRef<str>>(pattern: S) -> Self {
-        Self {
-            direction: TrimType::Both,
-            pattern: pattern.as_ref().into(),
-        }
+        Self::string(pattern, TrimDirection::Both)
     }
 
     /// Create a [`Trim`] item that will remove values from only the left side of a value
     pub fn left<S: AsRef<str>>(pattern: S) -> Self {
-        Self {
-            direction: TrimType::Left,
-            pattern: pattern.as_ref().into(),
-        }
+        Self::string(pattern, TrimDirection::Left)
     }
 
     /// Create a [`Trim`] item that will remove values from only the right side of a value
     pub fn right<S: AsRef<str>>(pattern: S) -> Self {
+        Self::string(pattern, TrimDirection::Right)
+    }
+
+    /// Create a [`Trim`] item that will remove Unicode whitespace from the provided side of a value
+    pub fn whitespace(direction: TrimDirection) -> Self {
         Self {
-            direction: TrimType::Right,
+            direction,
+            trim_type: TrimType::Whitespace,
+            pattern: String::new(),
+            start_regex: None,
+            end_regex: None,
+            limit: None,
+        }
+    }
+
+    /// Limit how many repetitions of the pattern are removed from each side of a value
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn string<S: AsRef<str>>(pattern: S, direction: TrimDirection) -> Self {
+        Self {
+            direction,
+            trim_type: TrimType::String,
             pattern: pattern.as_ref().into(),
+            start_regex: None,
+            end_regex: None,
+            limit: None,
         }
     }
 
     /// Process the provided value based on the [`Trim`] configuration
     pub fn trim<S: AsRef<str>>(&self, value: S) -> String {
-        match self.direction {
-            TrimType::Left => value.as_ref().trim_start_matches(&self.pattern).to_string(),
-            TrimType::Right => value.as_ref().trim_end_matches(&self.pattern).to_string(),
-            TrimType::Both => value
-                .as_ref()
-                .trim_start_matches(&self.pattern)
-                .trim_end_matches(&self.pattern)
-                .to_string(),
-        }
+        let value = value.as_ref();
+        let value = match self.direction {
+            TrimDirection::Left | TrimDirection::Both => self.repeat(value, Self::strip_start),
+            TrimDirection::Right => value,
+        };
+        let value = match self.direction {
+            TrimDirection::Right | TrimDirection::Both => self.repeat(value, Self::strip_end),
+            TrimDirection::Left => value,
+        };
+        value.to_string()
     }
 
     /// Process the provided value based on the [`Trim`] configuration for each value in the slice
     pub fn trim_slice<S: AsRef<str>>(&self, values: &[S]) -> Vec<String> {
         values.iter().map(|v| self.trim(v)).collect()
     }
+
+    fn repeat<'a>(&self, value: &'a str, strip: fn(&Self, &'a str) -> Option<&'a str>) -> &'a str {
+        let mut output = value;
+        let mut count = 0;
+        while self.limit.is_none_or(|limit| count < limit) {
+            match strip(self, output) {
+                Some(stripped) if stripped.len() < output.len() => output = stripped,
+                _ => break,
+            }
+            count += 1;
+        }
+        output
+    }
+
+    fn strip_start<'a>(&self, value: &'a str) -> Option<&'a str> {
+        match self.trim_type {
+            TrimType::String => value.strip_prefix(self.pattern.as_str()),
+            TrimType::Regex => self
+                .start_regex
+                .as_ref()
+                .and_then(|r| r.find(value))
+                .map(|m| &value[m.end()..]),
+            TrimType::Chars => value.strip_prefix(|c| self.pattern.contains(c)),
+            TrimType::Whitespace => value.strip_prefix(char::is_whitespace),
+        }
+    }
+
+    fn strip_end<'a>(&self, value: &'a str) -> Option<&'a str> {
+        match self.trim_type {
+            TrimType::String => value.strip_suffix(self.pattern.as_str()),
+            TrimType::Regex => self
+                .end_regex
+                .as_ref()
+                .and_then(|r| r.find(value))
+                .map(|m| &value[..m.start()]),
+            TrimType::Chars => value.strip_suffix(|c| self.pattern.contains(c)),
+            TrimType::Whitespace => value.strip_suffix(char::is_whitespace),
+        }
+    }
 }
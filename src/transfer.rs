@@ -0,0 +1,257 @@
+use crate::Error;
+use crate::path::absolute_path;
+use crate::processor::symlink::create_symlink;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, FileTimes, Metadata};
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+/// Used with the [`BatchBuilder`][crate::BatchBuilder] to choose how a copy is checked when a file is moved to another file system
+///
+/// Renaming across file systems is not possible so the file is copied, checked and only then removed from the source.
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum Verification {
+    /// Compare the size of the source and the copy
+    #[default]
+    Size,
+    /// Compare the size and a SHA-256 digest of the contents of the source and the copy
+    Checksum,
+}
+
+/// Used with the [`ProcessorBuilder`][crate::ProcessorBuilder] to choose what is done to a file at its new path
+///
+/// Every action other than [`FileAction::Move`] leaves the original file in place.
+#[derive(Debug, Default, Eq, PartialEq, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum FileAction {
+    /// Rename the file, copying it and removing the original when the new path is on another file system
+    #[default]
+    Move,
+    /// Copy the file or directory tree, keeping its metadata
+    Copy,
+    /// Create a hard link to the file
+    Hardlink,
+    /// Create a symbolic link with the absolute path of the file as its target
+    Symlink,
+    /// Create a symbolic link with the path of the file relative to the link as its target
+    RelativeSymlink,
+    /// Create a copy sharing the data of the file, only supported on Linux file systems with reflinks such as Btrfs and XFS
+    Reflink,
+}
+
+impl FileAction {
+    /// Whether the original file is left in place
+    pub(crate) fn keeps_source(&self) -> bool {
+        *self != FileAction::Move
+    }
+}
+
+/// Take the action to create the file at the new path
+///
+/// Missing parent directories of the destination are created and returned, outermost first.
+pub(crate) fn transfer(
+    from: &Path,
+    to: &Path,
+    action: FileAction,
+    verification: Verification,
+) -> Result<Vec<PathBuf>, Error> {
+    let created = create_parents(to)?;
+    let transferred = match action {
+        FileAction::Move => move_path(from, to, verification),
+        FileAction::Copy => copy_new(from, to, verification),
+        FileAction::Hardlink => Ok(std::fs::hard_link(from, to)?),
+        FileAction::Symlink => Ok(create_symlink(absolute_path(from).as_ref(), to, from)?),
+        FileAction::RelativeSymlink => {
+            Ok(create_symlink(relative_path(from, to).as_path(), to, from)?)
+        }
+        FileAction::Reflink => reflink(from, to),
+    };
+    match transferred {
+        Ok(_) => Ok(created),
+        Err(e) => {
+            remove_directories(created.as_slice());
+            Err(e)
+        }
+    }
+}
+
+// Rename the path, copying it and removing the source when the destination is on another file system
+fn move_path(from: &Path, to: &Path, verification: Verification) -> Result<(), Error> {
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            log::debug!("Copying {from:?} to {to:?} as they are on different file systems");
+            copy_new(from, to, verification)?;
+            remove_path(from)
+        }
+        renamed => Ok(renamed?),
+    }
+}
+
+// Copy the path, removing a partial copy when it fails
+fn copy_new(from: &Path, to: &Path, verification: Verification) -> Result<(), Error> {
+    let existed = to.symlink_metadata().is_ok();
+    copy_path(from, to, verification).inspect_err(|_| {
+        if let Some(Err(cleanup)) = (!existed).then(|| remove_path(to)) {
+            log::warn!("Unable to remove partial copy {to:?}: {cleanup}");
+        }
+    })
+}
+
+// Path of `target` relative to the directory of `link`
+fn relative_path(target: &Path, link: &Path) -> PathBuf {
+    let target = normalized(target);
+    let link = normalized(link);
+    let base = link.parent().unwrap_or(Path::new(""));
+    let common = target
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    base.components()
+        .skip(common)
+        .map(|_| Component::ParentDir)
+        .chain(target.components().skip(common))
+        .collect()
+}
+
+// Absolute path with `.` and `..` components resolved without following symbolic links
+fn normalized(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in absolute_path(path).components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(target_os = "linux")]
+fn reflink(from: &Path, to: &Path) -> Result<(), Error> {
+    use std::os::fd::AsRawFd;
+
+    let source = File::open(from)?;
+    let metadata = source.metadata()?;
+    let destination = File::options().write(true).create_new(true).open(to)?;
+    // SAFETY: both descriptors are open for the duration of the call and FICLONE takes the source descriptor as its argument
+    let cloned = unsafe { libc::ioctl(destination.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) };
+    drop(destination);
+    if cloned != 0 {
+        let error = std::io::Error::last_os_error();
+        std::fs::remove_file(to)?;
+        return Err(error.into());
+    }
+    preserve(to, &metadata)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink(_from: &Path, _to: &Path) -> Result<(), Error> {
+    Err(std::io::Error::from(ErrorKind::Unsupported).into())
+}
+
+/// Remove directories created by [`transfer`], innermost first, leaving any that are not empty
+pub(crate) fn remove_directories(created: &[PathBuf]) {
+    for directory in created.iter().rev() {
+        if let Err(e) = std::fs::remove_dir(directory) {
+            log::debug!("Unable to remove created directory {directory:?}: {e}");
+        }
+    }
+}
+
+// Create the missing parent directories of a path and return them, outermost first
+fn create_parents(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut missing: Vec<_> = path
+        .ancestors()
+        .skip(1)
+        .filter(|p| !p.as_os_str().is_empty())
+        .take_while(|p| p.symlink_metadata().is_err())
+        .map(Path::to_path_buf)
+        .collect();
+    missing.reverse();
+    if let Some(innermost) = missing.last() {
+        std::fs::create_dir_all(innermost)?;
+    }
+    Ok(missing)
+}
+
+// Copy a file, symbolic link or directory tree keeping permissions, ownership where allowed and modification times
+fn copy_path(from: &Path, to: &Path, verification: Verification) -> Result<(), Error> {
+    let metadata = from.symlink_metadata()?;
+    if metadata.is_symlink() {
+        create_symlink(std::fs::read_link(from)?.as_path(), to, from)?;
+        return Ok(());
+    }
+    if metadata.is_dir() {
+        std::fs::create_dir(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_path(
+                entry.path().as_path(),
+                to.join(entry.file_name()).as_path(),
+                verification,
+            )?;
+        }
+    } else {
+        std::fs::copy(from, to)?;
+        verify(from, to, &metadata, verification)?;
+    }
+    preserve(to, &metadata)
+}
+
+fn verify(
+    from: &Path,
+    to: &Path,
+    metadata: &Metadata,
+    verification: Verification,
+) -> Result<(), Error> {
+    let matches = to.metadata()?.len() == metadata.len()
+        && match verification {
+            Verification::Size => true,
+            Verification::Checksum => checksum(from)? == checksum(to)?,
+        };
+    match matches {
+        true => Ok(()),
+        false => Err(Error::VerificationFailed(to.into())),
+    }
+}
+
+// SHA-256 digest of the contents of a file
+fn checksum(path: &Path) -> Result<Vec<u8>, Error> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().to_vec())
+}
+
+// Permissions are kept by the copy of files, directories are set once their contents are copied
+fn preserve(path: &Path, metadata: &Metadata) -> Result<(), Error> {
+    std::fs::set_permissions(path, metadata.permissions())?;
+    preserve_owner(path, metadata);
+    let times = FileTimes::new()
+        .set_accessed(metadata.accessed()?)
+        .set_modified(metadata.modified()?);
+    File::open(path)?.set_times(times)?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn preserve_owner(path: &Path, metadata: &Metadata) {
+    use std::os::unix::fs::MetadataExt;
+    if let Err(e) = std::os::unix::fs::chown(path, Some(metadata.uid()), Some(metadata.gid())) {
+        log::debug!("Unable to keep the owner of {path:?}: {e}");
+    }
+}
+
+#[cfg(not(unix))]
+fn preserve_owner(_path: &Path, _metadata: &Metadata) {}
+
+pub(crate) fn remove_path(path: &Path) -> Result<(), Error> {
+    let metadata = path.symlink_metadata()?;
+    match metadata.is_dir() {
+        true => std::fs::remove_dir_all(path)?,
+        false => std::fs::remove_file(path)?,
+    }
+    Ok(())
+}
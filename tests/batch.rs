@@ -0,0 +1,554 @@
+use common::create_tree;
+use regex::Regex;
+use renamer_rs::{
+    BatchBuilder, CollisionKind, ConflictPolicy, Delimiter, DelimiterType, DictionaryMatch,
+    DirectoryInput, Error, FileAction, Format, InputFilter, InputType, Outcome, ProcessorBuilder,
+    Renamed, RenamedFile, Replacer, Report, SymlinkPolicy, Verification,
+};
+
+use std::path::Path;
+
+mod common;
+
+#[test]
+fn rename_directory_tree() {
+    let dir = create_tree(&[
+        "Show.S01/Show.S01E01.mkv",
+        "Show.S01/Extras.Part.1/Show.Featurette.mkv",
+    ]);
+    let directory = DirectoryInput::new(dir.path())
+        .recursive(true)
+        .include_directories(true);
+    let processor = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .stem_only(true)
+        .input_replacer(Replacer::new(Regex::new(r"\.").unwrap(), " "))
+        .input(InputType::Directory(directory));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.len(), 4);
+    assert_eq!(renamed.first().unwrap().future(), "Show S01");
+    BatchBuilder::new(renamed)
+        .apply()
+        .expect("Unable to apply batch");
+    assert!(dir.path().join("Show S01/Show S01E01.mkv").is_file());
+    assert!(
+        dir.path()
+            .join("Show S01/Extras Part 1/Show Featurette.mkv")
+            .is_file()
+    );
+}
+
+#[test]
+fn collisions_prevent_apply() {
+    let dir = create_tree(&["a.mkv", "b.mkv", "c.mkv", "C-S.mkv", "keep.txt"]);
+    let processor = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .input_replacer(Replacer::new(Regex::new(r"^[ab]\.mkv$").unwrap(), "ab.mkv"))
+        .input_replacer(Replacer::new(Regex::new(r"^c\.mkv$").unwrap(), "keep.txt"))
+        .input_replacer(Replacer::new(Regex::new(r"^C-S\.mkv$").unwrap(), "Ab.mkv"))
+        .input(InputType::new_directory(dir.path()));
+
+    let batch = BatchBuilder::new(processor.process().expect("Unable to process input"));
+    let kinds: Vec<_> = batch
+        .collisions()
+        .iter()
+        .map(|c| (c.path().file_name().unwrap().to_owned(), c.kind()))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("ab.mkv".into(), CollisionKind::Duplicate),
+            ("keep.txt".into(), CollisionKind::Duplicate),
+            ("Ab.mkv".into(), CollisionKind::CaseOnly),
+            ("ab.mkv".into(), CollisionKind::CaseOnly),
+        ]
+    );
+    assert!(matches!(batch.apply(), Err(Error::Collisions(_))));
+    assert!(dir.path().join("a.mkv").is_file());
+
+    let existing = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .input_replacer(Replacer::new(Regex::new(r"^c\.mkv$").unwrap(), "keep.txt"))
+        .input(InputType::new_file(dir.path().join("c.mkv")));
+    let batch = BatchBuilder::new(existing.process().expect("Unable to process input"));
+    assert!(
+        batch
+            .collisions()
+            .iter()
+            .any(|c| c.kind() == CollisionKind::Exists)
+    );
+}
+
+#[test]
+fn apply_chains_and_cycles() {
+    let dir = create_tree(&["01.mkv", "02.mkv", "03.mkv", "left.txt", "right.txt"]);
+    let mapping = vec![
+        ("01", "02"),
+        ("02", "03"),
+        ("03", "04"),
+        ("left", "right"),
+        ("right", "left"),
+    ];
+    let processor = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .stem_only(true)
+        .replacer(
+            Replacer::dictionary(mapping, DictionaryMatch::WholeSegment).expect("Invalid Replacer"),
+        )
+        .input(InputType::new_directory(dir.path()));
+
+    BatchBuilder::new(processor.process().expect("Unable to process input"))
+        .apply()
+        .expect("Unable to apply batch");
+    let mut names: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["02.mkv", "03.mkv", "04.mkv", "left.txt", "right.txt"]
+    );
+    for (name, content) in [
+        ("02.mkv", "01.mkv"),
+        ("04.mkv", "03.mkv"),
+        ("left.txt", "right.txt"),
+    ] {
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(name)).unwrap(),
+            content
+        );
+    }
+}
+
+#[test]
+fn apply_swap_inside_renamed_directory() {
+    let dir = create_tree(&["d/a.mkv", "d/b.mkv"]);
+    let mapping = vec![("a", "b"), ("b", "a"), ("d", "e")];
+    let directory = DirectoryInput::new(dir.path())
+        .recursive(true)
+        .include_directories(true);
+    let processor = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .stem_only(true)
+        .replacer(
+            Replacer::dictionary(mapping, DictionaryMatch::WholeSegment).expect("Invalid Replacer"),
+        )
+        .input(InputType::Directory(directory));
+
+    BatchBuilder::new(processor.process().expect("Unable to process input"))
+        .apply()
+        .expect("Unable to apply batch");
+    assert!(!dir.path().join("d").exists());
+    for (name, content) in [("e/a.mkv", "d/b.mkv"), ("e/b.mkv", "d/a.mkv")] {
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(name)).unwrap(),
+            content
+        );
+    }
+}
+
+#[test]
+fn failed_apply_rolls_back() {
+    let dir = create_tree(&["a.mkv", "b.mkv", "c.mkv"]);
+    let processor = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .input_replacer(Replacer::new(Regex::new(r"^").unwrap(), "new-"))
+        .input(InputType::new_directory(dir.path()));
+
+    let batch = BatchBuilder::new(processor.process().expect("Unable to process input"));
+    std::fs::remove_file(dir.path().join("c.mkv")).expect("Unable to remove file");
+    match batch.apply() {
+        Err(Error::RolledBack {
+            rolled_back,
+            failed,
+            ..
+        }) => {
+            assert_eq!(rolled_back.len(), 2);
+            assert!(failed.is_empty());
+        }
+        other => panic!("Expected rolled back batch, found {other:?}"),
+    }
+    assert!(dir.path().join("a.mkv").is_file());
+    assert!(dir.path().join("b.mkv").is_file());
+    assert!(!dir.path().join("new-a.mkv").exists());
+}
+
+#[test]
+fn journal_undo() {
+    let dir = create_tree(&["Show.S01/Show.S01E01.mkv", "Show.S01/Show.S01E02.mkv"]);
+    let journal = dir.path().join("journal.jsonl");
+    let apply = || {
+        let directory = DirectoryInput::new(dir.path().join("Show.S01"))
+            .recursive(true)
+            .include_directories(true);
+        let processor = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+            .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+            .stem_only(true)
+            .input_replacer(Replacer::new(Regex::new(r"\.").unwrap(), " "))
+            .input(InputType::new_file(dir.path().join("Show.S01")))
+            .input(InputType::Directory(directory));
+        BatchBuilder::new(processor.process().expect("Unable to process input"))
+            .journal(&journal)
+            .apply()
+            .expect("Unable to apply batch");
+        assert!(dir.path().join("Show S01/Show S01E02.mkv").is_file());
+    };
+
+    apply();
+    assert_eq!(
+        std::fs::read_to_string(&journal).unwrap().lines().count(),
+        3
+    );
+    BatchBuilder::undo(&journal).expect("Unable to undo batch");
+    assert!(dir.path().join("Show.S01/Show.S01E01.mkv").is_file());
+    assert!(!dir.path().join("Show S01").exists());
+    assert!(std::fs::read_to_string(&journal).unwrap().is_empty());
+    BatchBuilder::undo(&journal).expect("Unable to undo empty journal");
+
+    apply();
+    std::fs::write(dir.path().join("Show S01/Show S01E02.mkv"), "changed").unwrap();
+    assert!(matches!(
+        BatchBuilder::undo(&journal),
+        Err(Error::JournalConflicts(_))
+    ));
+    assert!(dir.path().join("Show S01/Show S01E01.mkv").is_file());
+}
+
+#[test]
+fn conflict_policies() {
+    let apply = |policy: ConflictPolicy, inputs: &[&str]| {
+        let dir = create_tree(&["a.mkv", "x.mkv", "y.mkv"]);
+        let processor = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+            .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+            .input_replacer(Replacer::new(Regex::new(r"^[xy]").unwrap(), "a"))
+            .inputs(
+                inputs
+                    .iter()
+                    .map(|i| InputType::new_file(dir.path().join(i)))
+                    .collect(),
+            );
+        let result = BatchBuilder::new(processor.process().expect("Unable to process input"))
+            .conflict_policy(policy)
+            .apply();
+        (dir, result)
+    };
+    let read =
+        |dir: &tempfile::TempDir, name: &str| std::fs::read_to_string(dir.path().join(name)).ok();
+
+    let (dir, result) = apply(ConflictPolicy::Fail, &["x.mkv"]);
+    assert!(matches!(result, Err(Error::Collisions(_))));
+    assert_eq!(read(&dir, "x.mkv").as_deref(), Some("x.mkv"));
+
+    let (dir, result) = apply(ConflictPolicy::Skip, &["x.mkv"]);
+    let report = result.expect("Unable to apply batch");
+    let entry = report.iter().next().unwrap();
+    assert!(
+        matches!(entry.outcome(), Outcome::Skipped(reason) if reason.ends_with("already exists"))
+    );
+    assert_eq!(read(&dir, "a.mkv").as_deref(), Some("a.mkv"));
+    assert_eq!(read(&dir, "x.mkv").as_deref(), Some("x.mkv"));
+
+    let (dir, result) = apply(ConflictPolicy::Overwrite, &["x.mkv"]);
+    assert!(result.is_ok());
+    assert_eq!(read(&dir, "a.mkv").as_deref(), Some("x.mkv"));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+
+    let (dir, result) = apply(ConflictPolicy::Overwrite, &["x.mkv", "y.mkv"]);
+    assert!(matches!(result, Err(Error::Collisions(_))));
+    assert_eq!(read(&dir, "a.mkv").as_deref(), Some("a.mkv"));
+
+    let (dir, result) = apply(ConflictPolicy::AutoSuffix, &["x.mkv", "y.mkv"]);
+    let report = result.expect("Unable to apply batch");
+    let futures: Vec<_> = report.iter().map(|e| e.future()).collect();
+    assert_eq!(futures, vec!["a (1).mkv", "a (2).mkv"]);
+    assert_eq!(read(&dir, "a.mkv").as_deref(), Some("a.mkv"));
+    assert_eq!(read(&dir, "a (1).mkv").as_deref(), Some("x.mkv"));
+    assert_eq!(read(&dir, "a (2).mkv").as_deref(), Some("y.mkv"));
+
+    let dir = create_tree(&["Season/old.mkv", "Season.new/new.mkv"]);
+    let processor = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+        .delimiter(Delimiter::new(".", DelimiterType::String).expect("Invalid Delimiter"))
+        .input(InputType::new_file(dir.path().join("Season.new")));
+    BatchBuilder::new(processor.process().expect("Unable to process input"))
+        .conflict_policy(ConflictPolicy::Overwrite)
+        .apply()
+        .expect("Unable to apply batch");
+    assert_eq!(
+        read(&dir, "Season/new.mkv").as_deref(),
+        Some("Season.new/new.mkv")
+    );
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn apply_reports_outcomes() {
+    let dir = create_tree(&["a.mkv", "b.mkv", "x.mkv", "keep.txt"]);
+    let processor = ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .input_replacer(Replacer::new(Regex::new(r"^b").unwrap(), "d"))
+        .input_replacer(Replacer::new(Regex::new(r"^x").unwrap(), "a"))
+        .filter(InputFilter::Extensions(vec!["mkv".to_string()]))
+        .inputs(
+            ["a.mkv", "b.mkv", "x.mkv", "keep.txt"]
+                .iter()
+                .map(|i| InputType::new_file(dir.path().join(i)))
+                .collect(),
+        );
+    let report = BatchBuilder::new(processor.process().expect("Unable to process input"))
+        .conflict_policy(ConflictPolicy::Skip)
+        .apply()
+        .expect("Unable to apply batch");
+
+    let outcomes: Vec<_> = report.iter().map(|e| (e.original(), e.outcome())).collect();
+    assert_eq!(outcomes.len(), 4);
+    assert!(
+        outcomes
+            .iter()
+            .any(|o| o.0.ends_with("a.mkv") && matches!(o.1, Outcome::Unchanged))
+    );
+    assert!(
+        outcomes
+            .iter()
+            .any(|o| o.0.ends_with("b.mkv") && matches!(o.1, Outcome::Renamed))
+    );
+    assert!(
+        outcomes
+            .iter()
+            .any(|o| o.0.ends_with("x.mkv") && matches!(o.1, Outcome::Skipped(_)))
+    );
+    assert!(
+        outcomes
+            .iter()
+            .any(|o| o.0.ends_with("keep.txt") && matches!(o.1, Outcome::Skipped(_)))
+    );
+    assert_eq!(
+        report.summary().to_string(),
+        "1 renamed, 1 unchanged, 2 skipped, 0 failed"
+    );
+    assert!(report.is_success());
+    assert!(dir.path().join("d.mkv").is_file());
+    assert!(dir.path().join("x.mkv").is_file());
+}
+
+#[cfg(unix)]
+#[test]
+fn apply_reports_links_not_updated() {
+    let dir = create_tree(&["pool/a.mkv", "pool/b.mkv"]);
+    let library = dir.path().join("library");
+    std::fs::create_dir(&library).expect("Unable to create directory");
+    for name in ["a.mkv", "b.mkv"] {
+        std::os::unix::fs::symlink(Path::new("../pool").join(name), library.join(name))
+            .expect("Unable to create link");
+    }
+    let processor = ProcessorBuilder::new(Format::new("%d1% New").expect("Invalid Format"))
+        .delimiter(Delimiter::new(".", DelimiterType::String).expect("Invalid Delimiter"))
+        .stem_only(true)
+        .symlink_policy(SymlinkPolicy::Target)
+        .input(InputType::new_directory(&library));
+    let batch = BatchBuilder::new(processor.process().expect("Unable to process input"));
+
+    // A directory in place of a link cannot be replaced by the new link
+    std::fs::remove_file(library.join("a.mkv")).expect("Unable to remove link");
+    std::fs::create_dir_all(library.join("a.mkv/keep")).expect("Unable to create directory");
+    let report = batch.apply().expect("Unable to apply batch");
+
+    let outcomes: Vec<_> = report.iter().map(|e| e.outcome()).collect();
+    assert!(matches!(outcomes[0], Outcome::LinkNotUpdated(_)));
+    assert!(matches!(outcomes[1], Outcome::Renamed));
+    assert!(!report.is_success());
+    assert!(dir.path().join("pool/a New.mkv").is_file());
+    assert_eq!(
+        std::fs::read_link(library.join("b.mkv")).unwrap(),
+        Path::new("../pool/b New.mkv")
+    );
+}
+
+#[test]
+fn report_apply_keeps_existing_files() {
+    let dir = create_tree(&["x.mkv", "y.mkv", "z.mkv"]);
+    let renamed: Vec<Box<dyn Renamed>> = vec![
+        Box::new(RenamedFile::new(dir.path().join("x.mkv"), "y.mkv")),
+        Box::new(RenamedFile::new(dir.path().join("z.mkv"), "w.mkv")),
+    ];
+    let report = Report::apply(renamed.as_slice());
+
+    let outcomes: Vec<_> = report.iter().map(|e| e.outcome()).collect();
+    assert!(matches!(outcomes[0], Outcome::Failed(Error::Collisions(_))));
+    assert!(matches!(outcomes[1], Outcome::Renamed));
+    for (name, content) in [("x.mkv", "x.mkv"), ("y.mkv", "y.mkv"), ("w.mkv", "z.mkv")] {
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(name)).unwrap(),
+            content
+        );
+    }
+}
+
+#[derive(Debug)]
+struct FailingText;
+
+impl Renamed for FailingText {
+    fn original(&self) -> &str {
+        "original"
+    }
+
+    fn future(&self) -> &str {
+        "future"
+    }
+
+    fn action(&self) -> Result<(), Error> {
+        Err(Error::InvalidValue("text cannot be renamed".to_string()))
+    }
+}
+
+#[test]
+fn apply_reports_failed_items() {
+    let dir = create_tree(&["a.mkv"]);
+    let renamed: Vec<Box<dyn Renamed>> = vec![
+        Box::new(FailingText),
+        Box::new(RenamedFile::new(dir.path().join("a.mkv"), "b.mkv")),
+    ];
+    let report = BatchBuilder::new(renamed)
+        .apply()
+        .expect("Unable to apply batch");
+
+    assert!(matches!(
+        report.entries().first().unwrap().outcome(),
+        Outcome::Failed(_)
+    ));
+    assert!(matches!(
+        report.entries().get(1).unwrap().outcome(),
+        Outcome::Renamed
+    ));
+    assert!(!report.is_success());
+    assert!(dir.path().join("b.mkv").is_file());
+}
+
+#[cfg(unix)]
+#[test]
+fn cross_device_move() {
+    use std::os::unix::fs::MetadataExt;
+    use std::time::{Duration, SystemTime};
+
+    // The staging area is on tmpfs and the library on the disk holding the temporary directory
+    let library = tempfile::tempdir().expect("Unable to create directory");
+    let staging = match tempfile::tempdir_in("/dev/shm") {
+        Ok(staging) => staging,
+        Err(e) => {
+            eprintln!("Skipping cross_device_move, /dev/shm is not available: {e}");
+            return;
+        }
+    };
+    if staging.path().metadata().unwrap().dev() == library.path().metadata().unwrap().dev() {
+        eprintln!("Skipping cross_device_move, /dev/shm is on the same file system");
+        return;
+    }
+    std::fs::create_dir(staging.path().join("Extras")).expect("Unable to create directory");
+    for name in ["a.mkv", "Extras/b.mkv"] {
+        std::fs::write(staging.path().join(name), name).expect("Unable to create file");
+    }
+    let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    std::fs::File::options()
+        .write(true)
+        .open(staging.path().join("a.mkv"))
+        .and_then(|f| f.set_modified(modified))
+        .expect("Unable to set modification time");
+
+    let directory = DirectoryInput::new(staging.path()).include_directories(true);
+    let processor = ProcessorBuilder::new(Format::new("Show/%d1%").expect("Invalid Format"))
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .destination(library.path())
+        .input(InputType::Directory(directory));
+    BatchBuilder::new(processor.process().expect("Unable to process input"))
+        .verification(Verification::Checksum)
+        .apply()
+        .expect("Unable to apply batch");
+
+    let moved = library.path().join("Show/a.mkv");
+    assert_eq!(std::fs::read_to_string(&moved).unwrap(), "a.mkv");
+    assert_eq!(moved.metadata().unwrap().modified().unwrap(), modified);
+    assert!(library.path().join("Show/Extras/b.mkv").is_file());
+    assert_eq!(std::fs::read_dir(staging.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn rollback_removes_created_directories() {
+    let dir = create_tree(&["Show.S01E01.mkv", "Show.S01E02.mkv"]);
+    let processor = ProcessorBuilder::new(Format::new("%d1%/%d2%.%d3%").expect("Invalid Format"))
+        .delimiter(Delimiter::new(".", DelimiterType::String).expect("Invalid Delimiter"))
+        .input(InputType::new_directory(dir.path()));
+
+    let batch = BatchBuilder::new(processor.process().expect("Unable to process input"));
+    std::fs::remove_file(dir.path().join("Show.S01E02.mkv")).expect("Unable to remove file");
+    assert!(matches!(batch.apply(), Err(Error::RolledBack { .. })));
+    assert!(dir.path().join("Show.S01E01.mkv").is_file());
+    assert!(!dir.path().join("Show").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn file_actions_keep_source() {
+    let dir = create_tree(&["seed/Show.S01E01.mkv"]);
+    let journal = dir.path().join("journal.jsonl");
+    let processor = |action: FileAction, format: &str| {
+        ProcessorBuilder::new(Format::new(format).expect("Invalid Format"))
+            .delimiter(Delimiter::new(".", DelimiterType::String).expect("Invalid Delimiter"))
+            .file_action(action)
+            .input(InputType::new_file(dir.path().join("seed/Show.S01E01.mkv")))
+    };
+
+    for (action, format) in [
+        (FileAction::Copy, "copy/%d1% %d2%.%d3%"),
+        (FileAction::Hardlink, "hardlink/%d1% %d2%.%d3%"),
+        (FileAction::Symlink, "symlink/%d1% %d2%.%d3%"),
+        (FileAction::RelativeSymlink, "relative/%d1% %d2%.%d3%"),
+    ] {
+        BatchBuilder::new(
+            processor(action, format)
+                .process()
+                .expect("Unable to process input"),
+        )
+        .journal(&journal)
+        .apply()
+        .expect("Unable to apply batch");
+    }
+
+    let source = dir.path().join("seed/Show.S01E01.mkv");
+    assert!(source.is_file());
+    for name in ["copy", "hardlink", "symlink", "relative"] {
+        let path = dir.path().join("seed").join(name).join("Show S01E01.mkv");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "seed/Show.S01E01.mkv"
+        );
+    }
+    assert_eq!(
+        std::fs::read_link(dir.path().join("seed/relative/Show S01E01.mkv")).unwrap(),
+        Path::new("../Show.S01E01.mkv")
+    );
+    assert!(
+        std::fs::read_link(dir.path().join("seed/symlink/Show S01E01.mkv"))
+            .unwrap()
+            .is_absolute()
+    );
+
+    // A copy that is renamed afterwards is removed from where it was moved to
+    let moved = ProcessorBuilder::new(Format::new("%d1% Pilot.%d2%").expect("Invalid Format"))
+        .delimiter(Delimiter::new(".", DelimiterType::String).expect("Invalid Delimiter"))
+        .input(InputType::new_file(
+            dir.path().join("seed/copy/Show S01E01.mkv"),
+        ));
+    BatchBuilder::new(moved.process().expect("Unable to process input"))
+        .journal(&journal)
+        .apply()
+        .expect("Unable to apply batch");
+    assert!(dir.path().join("seed/copy/Show S01E01 Pilot.mkv").is_file());
+
+    BatchBuilder::undo(&journal).expect("Unable to undo batch");
+    assert!(source.is_file());
+    for name in ["copy", "hardlink", "symlink", "relative"] {
+        let directory = dir.path().join("seed").join(name);
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0, "{name}");
+    }
+}
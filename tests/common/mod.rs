@@ -0,0 +1,12 @@
+use std::fs;
+use tempfile::TempDir;
+
+pub fn create_tree(files: &[&str]) -> TempDir {
+    let dir = tempfile::tempdir().expect("Unable to create directory");
+    for file in files {
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).expect("Unable to create directory");
+        fs::write(path, file).expect("Unable to create file");
+    }
+    dir
+}
@@ -0,0 +1,469 @@
+use common::create_tree;
+use renamer_rs::{
+    Deduplicate, Delimiter, DelimiterType, DirectoryInput, Error, FileKind, Format, GlobInput,
+    InputFilter, InputKind, InputSeparator, InputType, NonUtf8Policy, Outcome, ProcessorBuilder,
+    Sanitizer, SortMode, SymlinkPolicy,
+};
+use std::fs;
+use std::path::Path;
+
+mod common;
+
+const FILE_TREE: [&str; 6] = [
+    "a.mkv",
+    "b.txt",
+    ".hidden.mkv",
+    "sub/c.mkv",
+    "sub/deep/d.mkv",
+    "skip/e.mkv",
+];
+
+fn processor(format: &str) -> ProcessorBuilder {
+    let format = Format::new(format).expect("Invalid Format");
+    let delimiter = Delimiter::new(".", DelimiterType::String).expect("Invalid Delimiter");
+    ProcessorBuilder::new(format).delimiter(delimiter)
+}
+
+fn originals(processor: &ProcessorBuilder) -> Vec<String> {
+    processor
+        .process()
+        .expect("Unable to process input")
+        .iter()
+        .map(|r| r.original().to_string())
+        .collect()
+}
+
+#[test]
+fn directory_input() {
+    let dir = create_tree(&FILE_TREE);
+    let processor = processor("%d1%").input(InputType::new_directory(dir.path()));
+
+    assert_eq!(originals(&processor), vec!["a.mkv", "b.txt"]);
+}
+
+#[test]
+fn recursive_directory_input() {
+    let dir = create_tree(&FILE_TREE);
+    let directory = DirectoryInput::new(dir.path())
+        .recursive(true)
+        .max_depth(2)
+        .include("**.mkv")
+        .exclude("skip")
+        .include_hidden(true);
+    let processor = processor("%d1%").input(InputType::Directory(directory));
+
+    assert_eq!(originals(&processor), vec![".hidden.mkv", "a.mkv", "c.mkv"]);
+}
+
+#[test]
+fn directory_input_with_directories() {
+    let dir = create_tree(&FILE_TREE);
+    let directory = DirectoryInput::new(dir.path())
+        .recursive(true)
+        .include_directories(true)
+        .exclude("{skip,*.txt}");
+    let processor = processor("%d1%")
+        .input(InputType::new_file(dir.path().join(Path::new("a.mkv"))))
+        .input(InputType::Directory(directory));
+
+    assert_eq!(
+        originals(&processor),
+        vec!["a.mkv", "sub", "c.mkv", "deep", "d.mkv"]
+    );
+}
+
+#[test]
+fn glob_input() {
+    let dir = create_tree(&FILE_TREE);
+    let pattern = format!("{}/{{sub,skip}}/**/[c-d].mkv", dir.path().display());
+    let processor = processor("%d1%").input(InputType::new_glob(pattern));
+
+    assert_eq!(originals(&processor), vec!["c.mkv", "d.mkv"]);
+}
+
+#[test]
+fn glob_input_hidden() {
+    let dir = create_tree(&FILE_TREE);
+    let pattern = format!("{}/*.mkv", dir.path().display());
+    let visible = processor("%d1%").input(InputType::new_glob(pattern.as_str()));
+    assert_eq!(originals(&visible), vec!["a.mkv"]);
+
+    let glob = GlobInput::new(pattern).include_hidden(true);
+    let hidden = processor("%d1%").input(InputType::Glob(glob));
+    assert_eq!(originals(&hidden), vec![".hidden.mkv", "a.mkv"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn unreadable_directory() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = create_tree(&["locked/a.mkv", "b.mkv"]);
+    let locked = dir.path().join("locked");
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).expect("Unable to lock");
+    if fs::read_dir(&locked).is_ok() {
+        // Permissions are not enforced, as when running as root
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).expect("Unable to unlock");
+        return;
+    }
+
+    let directory = DirectoryInput::new(dir.path()).recursive(true);
+    let renamed = processor("%d1%")
+        .input(InputType::Directory(directory))
+        .input(InputType::new_glob(format!(
+            "{}/*/*.mkv",
+            dir.path().display()
+        )))
+        .process();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).expect("Unable to unlock");
+    let renamed = renamed.expect("Unable to process input");
+    let processed: Vec<_> = renamed
+        .iter()
+        .map(|r| (r.original(), r.skip_reason().is_some()))
+        .collect();
+    assert_eq!(processed, vec![("b.mkv", false), ("locked", true)]);
+}
+
+#[test]
+fn glob_input_no_matches() {
+    let dir = create_tree(&FILE_TREE);
+    let pattern = format!("{}/*.mp4", dir.path().display());
+    let processor = processor("%d1%").input(InputType::new_glob(pattern));
+
+    assert!(matches!(processor.process(), Err(Error::NoGlobMatches(_))));
+}
+
+#[cfg(unix)]
+#[test]
+fn non_utf8_file_name() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = tempfile::tempdir().expect("Unable to create directory");
+    let path = dir.path().join(OsStr::from_bytes(b"caf\xe9\\.01.txt"));
+    fs::write(&path, "").expect("Unable to create file");
+    let format = Format::new("%d2% - %d1%.%d3%").expect("Invalid Format");
+
+    let renamed = processor(format.value())
+        .input(InputType::new_file(&path))
+        .process()
+        .expect("Unable to process input");
+    let renamed = renamed.first().unwrap();
+    assert_eq!(renamed.original(), "caf\\xE9\\\\.01.txt");
+    assert_eq!(renamed.future(), "01 - caf\\xE9\\\\.txt");
+    renamed.action().expect("Unable to rename file");
+    assert!(
+        dir.path()
+            .join(OsStr::from_bytes(b"01 - caf\xe9\\.txt"))
+            .exists()
+    );
+
+    let skipped = processor(format.value())
+        .non_utf8_policy(NonUtf8Policy::Skip)
+        .input(InputType::new_directory(dir.path()))
+        .process()
+        .expect("Unable to process input");
+    assert!(skipped.first().unwrap().skip_reason().is_some());
+
+    let error = processor(format.value())
+        .non_utf8_policy(NonUtf8Policy::Error)
+        .input(InputType::new_directory(dir.path()))
+        .process();
+    assert!(matches!(error, Err(Error::NonUtf8FileName(_))));
+}
+
+#[cfg(unix)]
+#[test]
+fn sanitized_non_utf8_file_name() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = tempfile::tempdir().expect("Unable to create directory");
+    let path = dir.path().join(OsStr::from_bytes(b"caf\xe9\\x.txt"));
+    fs::write(&path, "").expect("Unable to create file");
+
+    let renamed = processor("%d1%.%d2%")
+        .sanitizer(Sanitizer::new())
+        .input(InputType::new_file(&path))
+        .process()
+        .expect("Unable to process input");
+    let renamed = renamed.first().unwrap();
+    assert_eq!(renamed.future(), "caf\\xE9_x.txt");
+    renamed.action().expect("Unable to rename file");
+    assert!(
+        dir.path()
+            .join(OsStr::from_bytes(b"caf\xe9_x.txt"))
+            .exists()
+    );
+}
+
+#[test]
+fn nul_reader() {
+    let dir = create_tree(&["first line.mkv", "second.mkv"]);
+    let stream = format!(
+        "{}\0{}\0",
+        dir.path().join("first line.mkv").display(),
+        dir.path().join("second.mkv").display()
+    );
+    let inputs = InputType::from_reader(stream.as_bytes(), InputSeparator::Nul, InputKind::File);
+    let processor = processor("%d2%-%d1%");
+
+    let renamed: Vec<_> = processor
+        .process_iter(inputs)
+        .collect::<Result<_, _>>()
+        .expect("Unable to process input");
+    assert_eq!(renamed.len(), 2);
+    assert_eq!(renamed.first().unwrap().future(), "mkv-first line");
+    assert_eq!(renamed.get(1).unwrap().future(), "mkv-second");
+}
+
+#[cfg(unix)]
+#[test]
+fn nul_reader_newline_in_name() {
+    let dir = create_tree(&["first\nline.mkv"]);
+    let stream = format!("{}\0", dir.path().join("first\nline.mkv").display());
+    let inputs = InputType::from_reader(stream.as_bytes(), InputSeparator::Nul, InputKind::File);
+
+    let renamed: Vec<_> = processor("%d2%-%d1%")
+        .process_iter(inputs)
+        .collect::<Result<_, _>>()
+        .expect("Unable to process input");
+    assert_eq!(renamed.len(), 1);
+    assert_eq!(renamed.first().unwrap().future(), "mkv-first\nline");
+}
+
+#[test]
+fn filtered_inputs() {
+    let dir = create_tree(&FILE_TREE);
+    let directory = DirectoryInput::new(dir.path())
+        .include_hidden(true)
+        .include_directories(true);
+    let processor = processor("%d1%")
+        .filter(InputFilter::FileType(FileKind::File))
+        .filter(InputFilter::Hidden(false))
+        .filter(InputFilter::Extensions(vec![".MKV".into()]))
+        .filter(InputFilter::Size {
+            min: Some(1),
+            max: Some(5),
+        })
+        .input(InputType::Directory(directory));
+
+    let renamed = processor.process().expect("Unable to process input");
+    let processed: Vec<_> = renamed
+        .iter()
+        .map(|r| (r.original(), r.skip_reason()))
+        .collect();
+    assert_eq!(
+        processed,
+        vec![
+            (".hidden.mkv", Some("input is hidden")),
+            ("a.mkv", None),
+            ("b.txt", Some("extension is not one of .MKV")),
+            ("skip", Some("input is not a regular file")),
+            ("sub", Some("input is not a regular file"))
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn filtered_dangling_symlink() {
+    let dir = create_tree(&["a.mkv"]);
+    std::os::unix::fs::symlink("missing.mkv", dir.path().join("b.mkv"))
+        .expect("Unable to create link");
+    let processor = processor("%d1%")
+        .follow_symlinks(true)
+        .filter(InputFilter::Size {
+            min: None,
+            max: Some(100),
+        })
+        .input(InputType::new_directory(dir.path()));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert!(renamed.first().unwrap().skip_reason().is_none());
+    let reason = renamed.get(1).unwrap().skip_reason().unwrap();
+    assert!(reason.starts_with("metadata could not be read"));
+}
+
+#[cfg(unix)]
+#[test]
+fn sorted_dangling_symlink() {
+    let dir = create_tree(&["b.mkv", "c.mkv"]);
+    std::os::unix::fs::symlink("missing.mkv", dir.path().join("a.mkv"))
+        .expect("Unable to create link");
+    let processor = processor("%d1%")
+        .follow_symlinks(true)
+        .sort(SortMode::Modified)
+        .reverse(true)
+        .input(InputType::new_directory(dir.path()));
+
+    let renamed = processor.process().expect("Unable to process input");
+    let last = renamed.last().unwrap();
+    assert_eq!(last.original(), "a.mkv");
+    assert!(
+        last.skip_reason()
+            .unwrap()
+            .starts_with("metadata could not be read")
+    );
+    assert!(renamed.iter().take(2).all(|r| r.skip_reason().is_none()));
+}
+
+#[cfg(unix)]
+#[test]
+fn symlink_target_policy() {
+    let dir = create_tree(&["pool/Show.S01E01.mkv", "pool/Show.S01E02.mkv"]);
+    let library = dir.path().join("library");
+    fs::create_dir(&library).expect("Unable to create directory");
+    std::os::unix::fs::symlink("../pool/Show.S01E01.mkv", library.join("link.mkv"))
+        .expect("Unable to create link");
+    std::os::unix::fs::symlink(
+        dir.path().join("pool/Show.S01E02.mkv"),
+        library.join("absolute.mkv"),
+    )
+    .expect("Unable to create link");
+
+    let skipped = processor("%d1% %d2%")
+        .symlink_policy(SymlinkPolicy::Skip)
+        .input(InputType::new_directory(&library))
+        .process()
+        .expect("Unable to process input");
+    assert!(skipped.iter().all(|r| r.skip_reason().is_some()));
+
+    let renamed = processor("%d1% %d2%")
+        .stem_only(true)
+        .symlink_policy(SymlinkPolicy::Target)
+        .input(InputType::new_directory(&library))
+        .process()
+        .expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().original(), "Show.S01E02.mkv");
+    for renamed in renamed {
+        renamed.action().expect("Unable to rename file");
+    }
+    assert_eq!(
+        fs::read_link(library.join("link.mkv")).unwrap(),
+        Path::new("../pool/Show S01E01.mkv")
+    );
+    assert_eq!(
+        fs::read_link(library.join("absolute.mkv")).unwrap(),
+        dir.path().join("pool/Show S01E02.mkv")
+    );
+    assert_eq!(
+        fs::read_to_string(library.join("link.mkv")).unwrap(),
+        "pool/Show.S01E01.mkv"
+    );
+
+    let renamed = processor("%d1% Pilot")
+        .stem_only(true)
+        .symlink_policy(SymlinkPolicy::Target)
+        .input(InputType::new_file(library.join("link.mkv")))
+        .process()
+        .expect("Unable to process input");
+    // A directory in place of the link cannot be replaced by the new link
+    fs::remove_file(library.join("link.mkv")).expect("Unable to remove link");
+    fs::create_dir(library.join("link.mkv")).expect("Unable to create directory");
+    fs::write(library.join("link.mkv/keep"), "").expect("Unable to create file");
+    let outcome = renamed.first().unwrap().apply();
+    assert!(matches!(outcome, Outcome::LinkNotUpdated(_)));
+    assert!(dir.path().join("pool/Show S01E01 Pilot.mkv").is_file());
+    assert!(library.join("link.mkv/keep").is_file());
+    assert_eq!(fs::read_dir(&library).unwrap().count(), 2);
+}
+
+#[test]
+fn deduplicated_inputs() {
+    let dir = create_tree(&FILE_TREE);
+    let processor = processor("%d1%")
+        .deduplicate(Deduplicate::CanonicalPath)
+        .input(InputType::new_file(dir.path().join("a.mkv")))
+        .input(InputType::new_file(dir.path().join("sub/../a.mkv")))
+        .input(InputType::new_file(dir.path().join("./b.txt")))
+        .input(InputType::new_file(dir.path().join("b.txt")));
+
+    let renamed = processor.process().expect("Unable to process input");
+    let processed: Vec<_> = renamed
+        .iter()
+        .map(|r| (r.original(), r.skip_reason().is_some()))
+        .collect();
+    assert_eq!(
+        processed,
+        vec![("a.mkv", false), ("a.mkv", true), ("b.txt", false)]
+    );
+    let reason = renamed.get(1).unwrap().skip_reason().unwrap();
+    assert!(reason.starts_with("input is a duplicate of"));
+}
+
+#[cfg(unix)]
+#[test]
+fn deduplicated_hard_links() {
+    let dir = create_tree(&FILE_TREE);
+    fs::hard_link(dir.path().join("a.mkv"), dir.path().join("z.mkv"))
+        .expect("Unable to create link");
+    let processor = processor("%d1%")
+        .deduplicate(Deduplicate::FileId)
+        .input(InputType::new_file(dir.path().join("a.mkv")))
+        .input(InputType::new_file(dir.path().join("z.mkv")));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert!(renamed.first().unwrap().skip_reason().is_none());
+    assert!(renamed.get(1).unwrap().skip_reason().is_some());
+}
+
+#[test]
+fn format_into_subdirectories() {
+    let dir = create_tree(&["Show.S01.E01.mkv", "Show.S01.E02.mkv", "Other.S02.E01.mkv"]);
+    let renamed = processor("%d1%/Season %d2%/%d1% %d2%%d3%.%d4%")
+        .input(InputType::new_directory(dir.path()))
+        .process()
+        .expect("Unable to process input");
+    assert_eq!(
+        renamed.first().unwrap().future(),
+        "Other/Season S02/Other S02E01.mkv"
+    );
+    for renamed in renamed {
+        renamed.action().expect("Unable to rename file");
+    }
+
+    for path in [
+        "Other/Season S02/Other S02E01.mkv",
+        "Show/Season S01/Show S01E01.mkv",
+        "Show/Season S01/Show S01E02.mkv",
+    ] {
+        assert!(dir.path().join(path).is_file());
+    }
+
+    for format in ["/Season %d2%/%d1%", "../%d1%", "%d1%/../../%d2%", "%d9%"] {
+        let skipped = processor(format)
+            .input(InputType::new_file(
+                dir.path().join("Show/Season S01/Show S01E01.mkv"),
+            ))
+            .process()
+            .expect("Unable to process input");
+        assert!(skipped.first().unwrap().skip_reason().is_some(), "{format}");
+    }
+}
+
+#[test]
+fn format_into_destination() {
+    let dir = create_tree(&["staging/Show.S01.E01.mkv", "staging/Show.S01.E02.mkv"]);
+    let library = dir.path().join("library");
+    let renamed = processor("%d1%/Season %d2%/%d1% %d2%%d3%.%d4%")
+        .destination(&library)
+        .input(InputType::new_directory(dir.path().join("staging")))
+        .process()
+        .expect("Unable to process input");
+    assert_eq!(
+        renamed.first().unwrap().future(),
+        "Show/Season S01/Show S01E01.mkv"
+    );
+    for renamed in renamed {
+        renamed.action().expect("Unable to rename file");
+    }
+
+    for path in [
+        "library/Show/Season S01/Show S01E01.mkv",
+        "library/Show/Season S01/Show S01E02.mkv",
+    ] {
+        assert!(dir.path().join(path).is_file());
+    }
+    assert_eq!(fs::read_dir(dir.path().join("staging")).unwrap().count(), 0);
+}
@@ -1,13 +1,15 @@
 use regex::Regex;
 use renamer_rs::{
-    Delimiter, DelimiterType, Extractor, Format, InputType, ProcessorBuilder, Replacer, Selector,
-    Trim,
+    Delimiter, DelimiterType, DictionaryMatch, Error, Extractor, Format, InputKind, InputSeparator,
+    InputType, NormalizationForm, Normalizer, ProcessorBuilder, Replacer, Sanitizer, Selector,
+    SortMode, Trim, TrimDirection, TrimType,
 };
 
 const TEXT_INPUT_1: &str = "Some simple text input to be split into segments other1 other2";
 const TEXT_INPUT_2: &str = "44343 $6556 troubled troubadour other values [] {} ?<>";
 const TEXT_INPUT_3: &str = "This is a title S03E04 - some other stuff.txt %e%";
 const TRIM_TEXT_INPUT_1: &str = "smiddles ssthings beforesafter";
+const TRIM_TEXT_INPUT_2: &str = "[(Show)]. -_Title_- 2024..";
 
 #[test]
 fn simple_string_delimiter() {
@@ -98,6 +100,51 @@ fn simple_trim() {
     );
 }
 
+#[test]
+fn regex_trim() {
+    let format = Format::new("%d1%-%d2%-%d3%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let trim = Trim::new(r"[\[(.]+", TrimType::Regex, TrimDirection::Left).expect("Invalid Trim");
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .trim(trim)
+        .input(InputType::new_text(TRIM_TEXT_INPUT_2));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(
+        renamed.first().unwrap().future(),
+        "Show)].--_Title_--2024.."
+    );
+}
+
+#[test]
+fn chars_trim() {
+    let format = Format::new("%d1%|%d2%|%d3%|%d4%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let trim = Trim::new("._-[]()", TrimType::Chars, TrimDirection::Both).expect("Invalid Trim");
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .trim(trim)
+        .input(InputType::new_text(TRIM_TEXT_INPUT_2));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "Show|Title|2024|");
+}
+
+#[test]
+fn limited_trim() {
+    let trim = Trim::new("._-[]()", TrimType::Chars, TrimDirection::Both)
+        .expect("Invalid Trim")
+        .limit(1);
+    assert_eq!(trim.trim("[(Show)]"), "(Show)");
+    assert_eq!(Trim::right(".").limit(1).trim("2024.."), "2024.");
+
+    let trim = Trim::whitespace(TrimDirection::Left);
+    assert_eq!(trim.trim("\u{3000}\t Title "), "Title ");
+    let trim = Trim::whitespace(TrimDirection::Both).limit(1);
+    assert_eq!(trim.trim("  Title\u{3000}"), " Title");
+}
+
 #[test]
 fn simple_replace() {
     let format = Format::new("%d1% --- %d9%").expect("Invalid Format");
@@ -115,3 +162,325 @@ fn simple_replace() {
     assert_eq!(renamed.get(1).unwrap().future(), "44343 --- !<>");
     assert_eq!(renamed.get(2).unwrap().future(), "Thi! --- !tuff.txt");
 }
+
+#[test]
+fn literal_replace() {
+    let format = Format::new("%d1% --- %d9%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let replace = Replacer::literal("s", "$1", true)
+        .expect("Invalid Replacer")
+        .limit(1);
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .replacer(replace)
+        .input(InputType::new_text(TEXT_INPUT_1))
+        .input(InputType::new_text(TEXT_INPUT_3));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "$1ome --- $1egments");
+    assert_eq!(renamed.get(1).unwrap().future(), "Thi$1 --- $1tuff.txt");
+}
+
+#[test]
+fn targeted_replace() {
+    let format = Format::new("%d1% %d2% %d9%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let by_position = Replacer::new(Regex::new("[aeiou]").unwrap(), "_").segments(vec![1, 9]);
+    let by_selector = Replacer::new(Regex::new("i").unwrap(), "!")
+        .selector(Selector::new(None, Regex::new("^s").unwrap()));
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .replacers(vec![by_position, by_selector])
+        .input(InputType::new_text(TEXT_INPUT_1));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "S_m_ s!mple s_gm_nts");
+}
+
+#[test]
+fn targeted_input_and_output_replacers() {
+    let targeted = [
+        Replacer::new(Regex::new("a").unwrap(), "b").segments(vec![1]),
+        Replacer::new(Regex::new("a").unwrap(), "b")
+            .selector(Selector::new(None, Regex::new("a").unwrap())),
+    ];
+    for replacer in targeted {
+        let processor = || {
+            ProcessorBuilder::new(Format::new("%d1%").expect("Invalid Format"))
+                .delimiter(Delimiter::default())
+                .input(InputType::new_text("a"))
+        };
+        let input = processor().input_replacer(replacer.clone());
+        assert!(matches!(input.process(), Err(Error::InvalidValue(_))));
+        let output = processor().output_replacer(replacer);
+        assert!(matches!(output.process(), Err(Error::InvalidValue(_))));
+        let mut renamed = output.process_iter([Ok(InputType::new_text("a"))]);
+        assert!(matches!(renamed.next(), Some(Err(Error::InvalidValue(_)))));
+        assert!(renamed.next().is_none());
+    }
+}
+
+#[test]
+fn input_and_output_replace() {
+    let format = Format::new("%d1% %d2% %d5% %d4%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .input_replacer(Replacer::new(Regex::new("[_.]").unwrap(), " "))
+        .output_replacer(Replacer::new(Regex::new(" {2,}").unwrap(), " "))
+        .input(InputType::new_text("Some_Show.Name.S01E02"));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "Some Show S01E02");
+}
+
+#[test]
+fn dictionary_replace() {
+    let format = Format::new("%d1% %d2% %d3% %d4%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let words = Replacer::dictionary(
+        vec![("Dr.", "Doctor"), ("teh", "the"), ("the", "THE")],
+        DictionaryMatch::WholeWord,
+    )
+    .expect("Invalid Replacer");
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .replacer(words)
+        .input(InputType::new_text("Dr. Who teh tehran"));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "Doctor Who the tehran");
+
+    let words = Replacer::dictionary(vec![("teh", "the")], DictionaryMatch::WholeWord)
+        .expect("Invalid Replacer")
+        .limit(1);
+    assert_eq!(words.replace("teh teh"), "the teh");
+    let segments = Replacer::dictionary(vec![("teh teh", "the")], DictionaryMatch::WholeSegment)
+        .expect("Invalid Replacer")
+        .limit(1);
+    assert_eq!(segments.replace("teh teh"), "the");
+}
+
+#[test]
+fn dictionary_file_replace() {
+    let dir = tempfile::tempdir().expect("Unable to create directory");
+    let path = dir.path().join("aliases.csv");
+    std::fs::write(
+        &path,
+        "# alias,title\nBSG,\"Battlestar Galactica, The\"\nTNG,The Next Generation\n",
+    )
+    .expect("Unable to write mapping file");
+    let format = Format::new("%d1% - %d2%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let titles = Replacer::dictionary_from_file(&path, DictionaryMatch::WholeSegment)
+        .expect("Invalid Replacer");
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .replacer(titles)
+        .input(InputType::new_text("BSG S01E01"))
+        .input(InputType::new_text("TNGS S01E01"));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(
+        renamed.first().unwrap().future(),
+        "Battlestar Galactica, The - S01E01"
+    );
+    assert_eq!(renamed.get(1).unwrap().future(), "TNGS - S01E01");
+}
+
+#[test]
+fn normalize_segments() {
+    let format = Format::new("%s1%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let selector = Selector::new(None, Regex::new("^Caf\u{e9}$").unwrap());
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .normalizer(Normalizer::new(NormalizationForm::Nfc).remove_zero_width(true))
+        .selector(selector)
+        .input(InputType::new_text("Le Cafe\u{301}\u{200B}"));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "Caf\u{e9}");
+}
+
+#[test]
+fn normalize_before_extractors() {
+    let format = Format::new("%e1%").expect("Invalid Format");
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(Delimiter::default())
+        .normalizer(Normalizer::new(NormalizationForm::Nfc).remove_zero_width(true))
+        .extractor(Extractor::new(None, Regex::new("Caf\u{e9} \\w+").unwrap()))
+        .input(InputType::new_text("Le Cafe\u{301} \u{200B}Noir"));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "Caf\u{e9} Noir");
+}
+
+#[test]
+fn normalize_before_input_replacers() {
+    let format = Format::new("%d1%").expect("Invalid Format");
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .normalizer(Normalizer::new(NormalizationForm::Nfc))
+        .input_replacer(Replacer::new(Regex::new("Caf\u{e9}").unwrap(), "Bar"))
+        .input(InputType::new_text("Cafe\u{301} Noir"));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "Bar Noir");
+}
+
+#[test]
+fn normalize_output_ascii() {
+    let format = Format::new("%d1% %d2%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .output_normalizer(Normalizer::new(NormalizationForm::Nfc).ascii(true))
+        .input(InputType::new_text("Caf\u{e9} \u{dc}ber"));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "Cafe Uber");
+}
+
+#[test]
+fn stem_only() {
+    let format = Format::new("%d1% --- %d9%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .stem_only(true)
+        .compound_extensions(vec![".en.srt", "tar.gz"])
+        .input(InputType::new_text(TEXT_INPUT_3))
+        .input(InputType::new_text(
+            "This is some other title with subtitles.EN.srt",
+        ))
+        .input(InputType::new_text(
+            "This is the backup of some really important stuff.tar.gz",
+        ))
+        .input(InputType::new_text(".hidden"))
+        .input(InputType::new_text(
+            "This is a title S03E04 - some other stuff.txt",
+        ));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(renamed.first().unwrap().future(), "This --- stuff.txt");
+    assert_eq!(renamed.get(1).unwrap().future(), "This --- .EN.srt");
+    assert_eq!(renamed.get(2).unwrap().future(), "This --- stuff.tar.gz");
+    assert_eq!(renamed.get(3).unwrap().future(), ".hidden --- ");
+    assert_eq!(renamed.get(4).unwrap().future(), "This --- stuff.txt");
+}
+
+#[test]
+fn newline_reader() {
+    let format = Format::new("%d1% --- %d9%").expect("Invalid Format");
+    let delimiter = Delimiter::default();
+    let processor = ProcessorBuilder::new(format).delimiter(delimiter);
+    let stream = format!("{TEXT_INPUT_1}\r\n\n{TEXT_INPUT_2}\n");
+    let inputs =
+        InputType::from_reader(stream.as_bytes(), InputSeparator::Newline, InputKind::Text);
+
+    let renamed: Vec<_> = processor
+        .process_iter(inputs)
+        .collect::<Result<_, _>>()
+        .expect("Unable to process input");
+    assert_eq!(renamed.len(), 2);
+    assert_eq!(renamed.first().unwrap().future(), "Some --- segments");
+    assert_eq!(renamed.get(1).unwrap().future(), "44343 --- ?<>");
+}
+
+#[test]
+fn sorted_inputs() {
+    let format = Format::new("%d1%").expect("Invalid Format");
+    let inputs = ["Episode 10", "episode 9", "Episode 2", "Episode 01"]
+        .iter()
+        .map(InputType::new_text)
+        .collect::<Vec<_>>();
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .extractor(Extractor::new(None, Regex::new(r"\d+").unwrap()))
+        .inputs(inputs);
+
+    let sorted = |processor: ProcessorBuilder| -> Vec<String> {
+        processor
+            .process_subset(3)
+            .expect("Unable to process input")
+            .iter()
+            .map(|r| r.future().to_string())
+            .collect()
+    };
+    assert_eq!(
+        sorted(processor.sort(SortMode::Natural)),
+        vec!["Episode 01", "Episode 2", "episode 9"]
+    );
+}
+
+#[test]
+fn reverse_sorted_inputs() {
+    let format = Format::new("%d1%").expect("Invalid Format");
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter"))
+        .extractor(Extractor::new(None, Regex::new(r"\d+").unwrap()))
+        .sort(SortMode::Extracted(1))
+        .reverse(true)
+        .input(InputType::new_text("Episode 10"))
+        .input(InputType::new_text("Special"))
+        .input(InputType::new_text("episode 9"))
+        .input(InputType::new_text("Episode 2"));
+
+    let renamed = processor.process().expect("Unable to process input");
+    let futures: Vec<_> = renamed.iter().map(|r| r.future()).collect();
+    assert_eq!(
+        futures,
+        vec!["Episode 10", "episode 9", "Episode 2", "Special"]
+    );
+}
+
+#[test]
+fn sanitized_output() {
+    let format = Format::new("%d1%/%d2%").expect("Invalid Format");
+    let delimiter = Delimiter::new("|", DelimiterType::String).expect("Invalid Delimiter");
+    let long = format!("{}.mkv", "é".repeat(200));
+    let processor = ProcessorBuilder::new(format)
+        .delimiter(delimiter)
+        .sanitizer(Sanitizer::new())
+        .input(InputType::new_text("What? <Live>: 1|con.txt"))
+        .input(InputType::new_text("..|Ends with dots.. "))
+        .input(InputType::new_text(format!("COM1|{long}")));
+
+    let renamed = processor.process().expect("Unable to process input");
+    assert_eq!(
+        renamed.first().unwrap().future(),
+        "What_ _Live__ 1/con_.txt"
+    );
+    assert_eq!(renamed.get(1).unwrap().future(), "Ends with dots");
+    let future = renamed.get(2).unwrap().future();
+    let (directory, name) = future.split_once('/').unwrap();
+    assert_eq!(directory, "COM1_");
+    assert_eq!(name.len(), 254);
+    assert!(name.ends_with("é.mkv"));
+
+    let flat = Sanitizer::new().directories(false).replacement("");
+    assert_eq!(flat.sanitize("a/b\0c*"), "abc");
+    assert_eq!(flat.sanitize("/"), "_");
+    assert_eq!(Sanitizer::new().sanitize("AC\\DC"), "AC_DC");
+}
+
+#[test]
+fn sanitized_empty_output() {
+    let sanitizer = Sanitizer::new();
+    for value in ["", "/", "..", "./..//", " . "] {
+        assert_eq!(sanitizer.sanitize(value), "_", "{value:?}");
+    }
+}
+
+#[test]
+fn sanitized_truncated_output() {
+    let sanitizer = Sanitizer::new().max_length(5);
+    assert_eq!(sanitizer.sanitize("abcdefgh.mkv"), "a.mkv");
+    assert_eq!(sanitizer.sanitize("é.mkv"), "é.mk");
+    assert_eq!(sanitizer.sanitize("a.longer"), "a.lon");
+
+    let sanitizer = Sanitizer::new().max_length(3);
+    assert_eq!(sanitizer.sanitize("CONxxxx"), "CO_");
+    assert_eq!(sanitizer.sanitize("aux.txt"), "au_");
+}